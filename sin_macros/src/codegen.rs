@@ -0,0 +1,91 @@
+use sin_types::{Delimiter, Group, TokenStream, TokenTree};
+
+/// Lexes a fragment of generated code.
+pub(crate) fn code(source: &str) -> TokenStream {
+    source
+        .parse()
+        .unwrap_or_else(|err| panic!("invalid generated code `{source}`: {err}"))
+}
+
+pub(crate) fn block(content: TokenStream) -> TokenTree {
+    Group::new(Delimiter::Brace, content).into()
+}
+
+pub(crate) fn paren(content: TokenStream) -> TokenTree {
+    Group::new(Delimiter::Paren, content).into()
+}
+
+pub(crate) fn bracket(content: TokenStream) -> TokenTree {
+    Group::new(Delimiter::Bracket, content).into()
+}
+
+/// Something that can be appended to generated code by [`gen!`].
+pub(crate) trait Emit {
+    fn emit(self, tokens: &mut TokenStream);
+}
+
+impl Emit for &str {
+    fn emit(self, tokens: &mut TokenStream) {
+        tokens.extend(code(self));
+    }
+}
+
+impl Emit for TokenStream {
+    fn emit(self, tokens: &mut TokenStream) {
+        tokens.extend(self);
+    }
+}
+
+impl Emit for &TokenStream {
+    fn emit(self, tokens: &mut TokenStream) {
+        tokens.extend(self);
+    }
+}
+
+impl Emit for TokenTree {
+    fn emit(self, tokens: &mut TokenStream) {
+        tokens.push(self);
+    }
+}
+
+impl Emit for &TokenTree {
+    fn emit(self, tokens: &mut TokenStream) {
+        tokens.push(self);
+    }
+}
+
+impl Emit for &[TokenTree] {
+    fn emit(self, tokens: &mut TokenStream) {
+        tokens.extend(TokenStream::from_tokens(self));
+    }
+}
+
+impl Emit for &Vec<TokenTree> {
+    fn emit(self, tokens: &mut TokenStream) {
+        tokens.extend(TokenStream::from_tokens(self));
+    }
+}
+
+/// Concatenates code fragments (`&str`s of balanced source text, [`TokenStream`]s and
+/// [`TokenTree`]s) into a single [`TokenStream`] of generated code.
+macro_rules! gen {
+    ($($part:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut tokens = ::sin_types::TokenStream::new();
+        $($crate::codegen::Emit::emit($part, &mut tokens);)*
+        tokens
+    }};
+}
+pub(crate) use gen;
+
+/// Joins `items` with `separator`, i.e. to build comma separated lists of generated code.
+pub(crate) fn join(items: impl IntoIterator<Item = TokenStream>, separator: &str) -> TokenStream {
+    let mut tokens = TokenStream::new();
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            tokens.extend(code(separator));
+        }
+        tokens.extend(item);
+    }
+    tokens
+}
@@ -0,0 +1,411 @@
+use crate::{
+    codegen::{block, bracket, gen, join, paren},
+    item::{Data, Field, Fields, GenericParamKind, Item, SinArg, Variant},
+};
+use sin_types::{Delimiter, ParseError, ParseResult, Punct, Token, TokenStream, TokenTree};
+
+/// Generates `impl<..> #trait_path for #name<..> where ..`, adding a `Parse` bound for every
+/// type parameter.
+fn impl_header(item: &Item, trait_path: &str) -> TokenStream {
+    let params = &item.generics.params;
+    let mut header = gen!("#[automatically_derived] impl");
+    if !params.is_empty() {
+        let decls = params.iter().map(|param| gen!(&param.decl));
+        header.extend(gen!("<", join(decls, ","), ">"));
+    }
+    header.extend(gen!(trait_path, "for", TokenTree::from(item.ident)));
+    if !params.is_empty() {
+        let names = params.iter().map(|param| gen!(&param.name));
+        header.extend(gen!("<", join(names, ","), ">"));
+    }
+    let predicates = &item.generics.predicates;
+    header.extend(gen!("where", predicates));
+    if !matches!(
+        predicates.last(),
+        None | Some(TokenTree::Leaf(Token::Punct(Punct::Comma), _))
+    ) {
+        header.extend(gen!(","));
+    }
+    for param in params {
+        if let GenericParamKind::Type = param.kind {
+            header.extend(gen!(&param.name, ": ::sin::types::Parse,"));
+        }
+    }
+    header
+}
+
+/// Returns a pattern destructuring `fields` (i.e. `{ a, b }` or `(__sin_0, __sin_1)`) along
+/// with each field and its binding.
+fn destructure(fields: &Fields) -> (TokenStream, Vec<(&Field, TokenStream)>) {
+    match fields {
+        Fields::Named(fields) => {
+            let bindings: Vec<(&Field, TokenStream)> = fields
+                .iter()
+                .map(|field| {
+                    let ident = field.ident.expect("named field");
+                    (field, gen!(TokenTree::from(ident)))
+                })
+                .collect();
+            let names = bindings.iter().map(|(_, binding)| binding.clone());
+            (gen!(block(join(names, ","))), bindings)
+        }
+        Fields::Unnamed(fields) => {
+            let bindings: Vec<(&Field, TokenStream)> = fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| (field, gen!(format!("__sin_{i}").as_str())))
+                .collect();
+            let names = bindings.iter().map(|(_, binding)| binding.clone());
+            (gen!(paren(join(names, ","))), bindings)
+        }
+        Fields::Unit => (TokenStream::new(), Vec::new()),
+    }
+}
+
+/// How a field is parsed, as set by at most one of its `#[sin(..)]` arguments.
+#[derive(Default)]
+enum FieldMode<'a> {
+    #[default]
+    Plain,
+    Delimited(Delimiter),
+    Until(&'a [TokenTree]),
+    Terminated,
+}
+
+/// The `#[sin(..)]` arguments of a field, controlling how it is parsed.
+#[derive(Default)]
+struct FieldArgs<'a> {
+    mode: FieldMode<'a>,
+    optional_if: Option<&'a [TokenTree]>,
+}
+
+impl<'a> FieldArgs<'a> {
+    fn parse(field: &'a Field) -> ParseResult<Self> {
+        let mut args = FieldArgs::default();
+        let mut mode_arg: Option<&SinArg> = None;
+        for arg in &field.args {
+            let key = arg.key.ident.as_str();
+            let expects_value = matches!(key, "optional_if" | "until");
+            match (expects_value, arg.value.is_empty()) {
+                (true, true) => {
+                    return Err(ParseError::new()
+                        .error(arg.key.span, format!("expected `{key} = pat![..]`")))
+                }
+                (false, false) if field_delimiter(key).is_some() || key == "terminated" => {
+                    return Err(ParseError::new()
+                        .error(arg.key.span, format!("`{key}` does not take a value")))
+                }
+                _ => (),
+            }
+            match key {
+                "optional_if" => {
+                    args.optional_if = Some(&arg.value);
+                    continue;
+                }
+                "terminated" => args.mode = FieldMode::Terminated,
+                "until" => args.mode = FieldMode::Until(&arg.value),
+                key => match field_delimiter(key) {
+                    Some(delimiter) => args.mode = FieldMode::Delimited(delimiter),
+                    None => {
+                        return Err(ParseError::new()
+                            .error(arg.key.span, format!("unknown field attribute `{key}`")))
+                    }
+                },
+            }
+            if let Some(previous) = mode_arg.replace(arg) {
+                return Err(ParseError::new().error(
+                    arg.key.span,
+                    format!("`{key}` cannot be combined with `{}`", previous.key.ident),
+                ));
+            }
+        }
+        Ok(args)
+    }
+}
+
+/// Returns the [`Delimiter`] corresponding with a `paren`, `brace` or `bracket` attribute.
+fn field_delimiter(key: &str) -> Option<Delimiter> {
+    match key {
+        "paren" => Some(Delimiter::Paren),
+        "brace" => Some(Delimiter::Brace),
+        "bracket" => Some(Delimiter::Bracket),
+        _ => None,
+    }
+}
+
+/// Returns the `#[sin(paren)]`, `#[sin(brace)]` or `#[sin(bracket)]` [`Delimiter`] of a field.
+fn delimiter(field: &Field) -> Option<Delimiter> {
+    field
+        .args
+        .iter()
+        .find_map(|arg| field_delimiter(arg.key.ident.as_str()))
+}
+
+fn delimiter_path(delimiter: Delimiter) -> TokenStream {
+    gen!(format!("::sin::types::Delimiter::{delimiter:?}").as_str())
+}
+
+/// Generates an expression parsing `field` from `__sin_input` according to its `#[sin(..)]`
+/// arguments.
+fn parse_field(field: &Field) -> ParseResult<TokenStream> {
+    let args = FieldArgs::parse(field)?;
+    // `optional_if` fields are `Option`s of the parsed type, so it is left to inference
+    let turbofish = match args.optional_if {
+        Some(_) => TokenStream::new(),
+        None => gen!("::<", &field.ty, ">"),
+    };
+    let parse = match args.mode {
+        FieldMode::Delimited(delimiter) => gen!(
+            "__sin_input.parse_delimited",
+            turbofish,
+            paren(delimiter_path(delimiter)),
+        ),
+        FieldMode::Until(pattern) => {
+            gen!("__sin_input.parse_until", turbofish, paren(gen!(pattern)))
+        }
+        FieldMode::Terminated => gen!("__sin_input.parse_terminated", turbofish, "()"),
+        FieldMode::Plain => gen!("__sin_input.parse", turbofish, "()"),
+    };
+    Ok(match args.optional_if {
+        Some(pattern) => gen!(
+            "if __sin_input.peek_matches",
+            paren(gen!(pattern)),
+            block(gen!(
+                "::core::option::Option::Some",
+                paren(gen!(parse, "?"))
+            )),
+            "else { ::core::option::Option::None }",
+        ),
+        None => gen!(parse, "?"),
+    })
+}
+
+/// Generates an expression constructing `path` (i.e. `Self` or `Self::Variant`) by parsing
+/// each of `fields` in order from `__sin_input`.
+fn construct(path: TokenStream, fields: &Fields) -> ParseResult<TokenStream> {
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let mut inits = Vec::new();
+            for field in fields {
+                let ident = field.ident.expect("named fields have idents");
+                inits.push(gen!(TokenTree::from(ident), ":", parse_field(field)?));
+            }
+            gen!(path, block(join(inits, ",")))
+        }
+        Fields::Unnamed(fields) => {
+            let parsed = fields.iter().map(parse_field);
+            gen!(
+                path,
+                paren(join(parsed.collect::<ParseResult<Vec<_>>>()?, ","))
+            )
+        }
+        Fields::Unit => path,
+    })
+}
+
+fn variant_path(variant: &Variant) -> TokenStream {
+    gen!("Self::", TokenTree::from(variant.ident))
+}
+
+/// Generates the statements that try to parse `variant` as one of the [`Alternatives`] of
+/// an enum, returning from the `parse` fn if it matches.
+///
+/// [`Alternatives`]: sin_types::Alternatives
+fn parse_variant(variant: &Variant) -> ParseResult<TokenStream> {
+    let construct = construct(variant_path(variant), &variant.fields)?;
+    let mut peek = None;
+    for arg in &variant.args {
+        match arg.key.ident.as_str() {
+            "peek" if !arg.value.is_empty() => peek = Some(&arg.value),
+            "peek" => {
+                return Err(ParseError::new().error(arg.key.span, "expected `peek = pat![..]`"))
+            }
+            key => {
+                return Err(ParseError::new()
+                    .error(arg.key.span, format!("unknown variant attribute `{key}`")))
+            }
+        }
+    }
+    Ok(match peek {
+        Some(pattern) => gen!(
+            "if __sin_alternatives.peek",
+            paren(gen!("__sin_input,", pattern)),
+            block(gen!(
+                "return ::core::result::Result::Ok",
+                paren(construct),
+                ";"
+            )),
+        ),
+        None => gen!(
+            "if let ::core::option::Option::Some(__sin_value) = __sin_alternatives.attempt",
+            paren(gen!(
+                "__sin_input, |__sin_input| ::core::result::Result::Ok",
+                paren(construct),
+            )),
+            block(gen!("return ::core::result::Result::Ok(__sin_value);")),
+        ),
+    })
+}
+
+fn parse_fn(item: &Item) -> ParseResult<TokenStream> {
+    let body = match &item.data {
+        Data::Struct(fields) => gen!(
+            "::core::result::Result::Ok",
+            paren(construct(gen!("Self"), fields)?),
+        ),
+        Data::Enum(variants) => {
+            let mut body = gen!("let mut __sin_alternatives = ::sin::types::Alternatives::new();");
+            for variant in variants {
+                body.extend(parse_variant(variant)?);
+            }
+            body.extend(gen!(
+                "::core::result::Result::Err(__sin_alternatives.into_error(__sin_input))"
+            ));
+            body
+        }
+    };
+    let mut parse = gen!(
+        "fn parse<'__sin_a, __SinState: ::core::default::Default + ::core::clone::Clone>(
+            __sin_input: &mut ::sin::types::Parser<'__sin_a, __SinState>,
+        ) -> ::sin::types::ParseResult<Self>",
+        block(body),
+    );
+    // only types without generic type or lifetime parameters are known to be `'static`
+    let memoizable = item
+        .generics
+        .params
+        .iter()
+        .all(|param| matches!(param.kind, GenericParamKind::Const));
+    if memoizable {
+        parse.extend(gen!(
+            "fn parse_memoized<'__sin_a, __SinState: ::core::default::Default + ::core::clone::Clone>(
+                __sin_input: &mut ::sin::types::Parser<'__sin_a, __SinState>,
+            ) -> ::sin::types::ParseResult<Self> {
+                __sin_input.parse_memo::<Self>()
+            }"
+        ));
+    }
+    Ok(parse)
+}
+
+/// Generates a `match self { .. }` expression evaluating `arm` for the fields (and their
+/// bindings) of the struct or of whichever enum variant `self` is.
+fn match_fields(item: &Item, arm: impl Fn(&[(&Field, TokenStream)]) -> TokenStream) -> TokenStream {
+    let arms = match &item.data {
+        Data::Struct(fields) => {
+            let (pattern, bindings) = destructure(fields);
+            vec![gen!("Self", pattern, "=>", block(arm(&bindings)))]
+        }
+        Data::Enum(variants) if variants.is_empty() => return gen!("match *self {}"),
+        Data::Enum(variants) => variants
+            .iter()
+            .map(|variant| {
+                let (pattern, bindings) = destructure(&variant.fields);
+                gen!(variant_path(variant), pattern, "=>", block(arm(&bindings)))
+            })
+            .collect(),
+    };
+    gen!("match self", block(join(arms, ",")))
+}
+
+fn to_token_stream_fn(item: &Item) -> TokenStream {
+    let to_tokens = match_fields(item, |bindings| {
+        let mut stmts = TokenStream::new();
+        for (field, binding) in bindings {
+            let tokens = gen!(
+                "::sin::types::ToTokens::to_token_stream",
+                paren(binding.clone())
+            );
+            // delimited fields are re-wrapped in a group spanning their content
+            stmts.extend(match delimiter(field) {
+                Some(delimiter) => gen!(
+                    "::sin::types::__private::push_group",
+                    paren(gen!(
+                        "&mut __sin_tokens,",
+                        delimiter_path(delimiter),
+                        ",",
+                        tokens,
+                        ", ::sin::types::Spanned::span",
+                        paren(binding.clone()),
+                    )),
+                    ";",
+                ),
+                None => gen!("__sin_tokens.extend", paren(tokens), ";"),
+            });
+        }
+        stmts
+    });
+    gen!(
+        "fn to_token_stream(&self) -> ::sin::types::TokenStream",
+        block(gen!(
+            "let mut __sin_tokens = ::sin::types::TokenStream::new();",
+            to_tokens,
+            "__sin_tokens",
+        )),
+    )
+}
+
+fn span_fn(item: &Item) -> TokenStream {
+    let span = match_fields(item, |bindings| {
+        let spans = bindings
+            .iter()
+            .map(|(_, binding)| gen!("::sin::types::Spanned::span", paren(binding.clone())));
+        gen!(
+            "::sin::types::__private::join_spans",
+            paren(gen!("&", bracket(join(spans, ",")))),
+        )
+    });
+    gen!("fn span(&self) -> ::sin::types::Span", block(span))
+}
+
+/// Generates the `Parse`, `ToTokens` and `Spanned` impls for a struct or enum.
+///
+/// Structs are parsed by parsing each of their fields in order, while enums are parsed by
+/// trying each of their variants in order as [`Alternatives`](sin_types::Alternatives).
+pub(crate) fn derive_parse(tokens: &TokenStream) -> ParseResult<TokenStream> {
+    let item = Item::parse(tokens, "Parse")?;
+    Ok(gen!(
+        impl_header(&item, "::sin::types::Parse"),
+        block(parse_fn(&item)?),
+        impl_header(&item, "::sin::types::ToTokens"),
+        block(to_token_stream_fn(&item)),
+        impl_header(&item, "::sin::types::Spanned"),
+        block(span_fn(&item)),
+    ))
+}
+
+#[test]
+fn test_derive_parse_field_attr_errors() {
+    let derive_error = |source: &str| {
+        let tokens: TokenStream = source.parse().unwrap();
+        derive_parse(&tokens).err().unwrap().messages[0].message
+    };
+    assert_eq!(
+        derive_error("struct Foo(#[sin(paren, brace)] Ident);"),
+        "`brace` cannot be combined with `paren`"
+    );
+    assert_eq!(
+        derive_error("struct Foo { #[sin(until)] a: Ident }"),
+        "expected `until = pat![..]`"
+    );
+    assert_eq!(
+        derive_error("struct Foo { #[sin(terminated = true)] a: Ident }"),
+        "`terminated` does not take a value"
+    );
+    assert_eq!(
+        derive_error("struct Foo(#[sin(paren, until = pat![;])] Ident);"),
+        "`until` cannot be combined with `paren`"
+    );
+    assert_eq!(
+        derive_error("struct Foo(#[sin(terminated, bracket)] Ident);"),
+        "`bracket` cannot be combined with `terminated`"
+    );
+    assert_eq!(
+        derive_error("struct Foo { #[sin(skip)] a: Ident }"),
+        "unknown field attribute `skip`"
+    );
+    let tokens: TokenStream = "struct Foo(#[sin(bracket, optional_if = pat![[]])] Option<Ident>);"
+        .parse()
+        .unwrap();
+    assert!(derive_parse(&tokens).is_ok());
+}
@@ -0,0 +1,193 @@
+//! The `#[sin::proc_macro]`, `#[sin::proc_macro_attribute]` and `#[sin::proc_macro_derive]`
+//! attributes, which wrap a function taking parsed input in the glue of a proc macro.
+
+use crate::{
+    codegen::{block, bracket, gen, paren},
+    item::{group, is_keyword, is_punct, until_comma},
+};
+use sin_types::{
+    token_stream::Peekable, Delimiter, Ident, Keyword, ParseError, ParseResult, Parser, Punct,
+    Span, Spanned, TokenStream, TokenTree,
+};
+
+#[derive(Copy, Clone)]
+pub(crate) enum EntryPoint {
+    Function,
+    Attribute,
+    Derive,
+}
+
+impl EntryPoint {
+    fn name(self) -> &'static str {
+        match self {
+            EntryPoint::Function => "proc_macro",
+            EntryPoint::Attribute => "proc_macro_attribute",
+            EntryPoint::Derive => "proc_macro_derive",
+        }
+    }
+
+    /// The number of arguments the wrapped function takes.
+    fn arity(self) -> usize {
+        match self {
+            EntryPoint::Attribute => 2,
+            EntryPoint::Function | EntryPoint::Derive => 1,
+        }
+    }
+}
+
+/// Generates a proc macro named after the function in `item`, which is moved into its body
+/// and called by `sin_types::__private::proc_macro` (or `proc_macro_attribute`) with the
+/// parsed input.
+pub(crate) fn entry_point(
+    kind: EntryPoint,
+    args: &TokenStream,
+    item: &TokenStream,
+) -> ParseResult<TokenStream> {
+    let name = kind.name();
+    let mut input: Parser = item.to_parser();
+    // attributes and docs are moved to the generated proc macro, so that they are documented
+    let mut attrs = Vec::new();
+    while is_punct(input.peek().as_ref(), Punct::Pound)
+        && group(input.peek_n(2).as_ref(), Delimiter::Bracket).is_some()
+    {
+        attrs.extend(input.next());
+        attrs.extend(input.next());
+    }
+    let mut vis = Vec::new();
+    if is_keyword(input.peek().as_ref(), Keyword::Pub) {
+        vis.extend(input.next());
+        if group(input.peek().as_ref(), Delimiter::Paren).is_some() {
+            vis.extend(input.next());
+        }
+    }
+    let function = TokenStream::from_tokens(&input.clone().collect::<Vec<_>>());
+    match input.next() {
+        Some(tt) if is_keyword(Some(&tt), Keyword::Fn) => (),
+        Some(tt) => {
+            let message = format!("`#[sin::{name}]` can only be applied to a `fn`");
+            return Err(ParseError::new().error(tt.span(), message));
+        }
+        None => return Err(ParseError::new().error(Span::call_site(), "expected `fn`")),
+    }
+    let ident: Ident = input.parse()?;
+    if let Some(lt) = input.peek().filter(|tt| is_punct(Some(tt), Punct::Lt)) {
+        let message = format!("`#[sin::{name}]` functions cannot be generic");
+        return Err(ParseError::new().error(lt.span(), message));
+    }
+    let Some(params) = group(input.peek().as_ref(), Delimiter::Paren) else {
+        return Err(ParseError::new().error(ident.span, "expected function parameters"));
+    };
+    let mut params_input: Parser = params.content.to_parser();
+    let mut arity = 0;
+    while params_input.peek().is_some() {
+        until_comma(&mut params_input);
+        params_input.next();
+        arity += 1;
+    }
+    if arity != kind.arity() {
+        let message = match kind {
+            EntryPoint::Attribute => {
+                format!("`#[sin::{name}]` functions take the attribute arguments and the item")
+            }
+            _ => format!("`#[sin::{name}]` functions take a single parsed argument"),
+        };
+        return Err(ParseError::new().error(params.span, message));
+    }
+    let attr = match kind {
+        EntryPoint::Derive if args.is_empty() => {
+            let message = "expected the name of the derive, i.e. `#[sin::proc_macro_derive(Name)]`";
+            return Err(ParseError::new().error(Span::call_site(), message));
+        }
+        EntryPoint::Derive => gen!(name, paren(args.clone())),
+        _ if !args.is_empty() => {
+            let message = format!("`#[sin::{name}]` does not take arguments");
+            let span = args.iter().next().map_or(Span::call_site(), |tt| tt.span());
+            return Err(ParseError::new().error(span, message));
+        }
+        _ => gen!(name),
+    };
+    let ident = TokenTree::from(ident);
+    let signature = match kind {
+        EntryPoint::Attribute => {
+            "(__sin_attr: ::proc_macro::TokenStream, __sin_item: ::proc_macro::TokenStream)"
+        }
+        _ => "(__sin_input: ::proc_macro::TokenStream)",
+    };
+    let (call, args) = match kind {
+        EntryPoint::Attribute => (
+            "::sin::types::__private::proc_macro_attribute",
+            "__sin_attr, __sin_item,",
+        ),
+        _ => ("::sin::types::__private::proc_macro", "__sin_input,"),
+    };
+    Ok(gen!(
+        &attrs,
+        "#",
+        bracket(attr),
+        &vis,
+        "fn",
+        &ident,
+        signature,
+        "-> ::proc_macro::TokenStream",
+        block(gen!(function, call, paren(gen!(args, &ident)))),
+    ))
+}
+
+#[test]
+fn test_entry_point_errors() {
+    let entry_point_error = |kind: EntryPoint, args: &str, source: &str| {
+        let args: TokenStream = args.parse().unwrap();
+        let tokens: TokenStream = source.parse().unwrap();
+        entry_point(kind, &args, &tokens).err().unwrap().messages[0].message
+    };
+    assert_eq!(
+        entry_point_error(EntryPoint::Function, "", "struct Foo;"),
+        "`#[sin::proc_macro]` can only be applied to a `fn`"
+    );
+    assert_eq!(
+        entry_point_error(EntryPoint::Function, "", "fn foo<T>(input: T) {}"),
+        "`#[sin::proc_macro]` functions cannot be generic"
+    );
+    assert_eq!(
+        entry_point_error(EntryPoint::Function, "", "fn foo(a: Ident, b: Ident) {}"),
+        "`#[sin::proc_macro]` functions take a single parsed argument"
+    );
+    assert_eq!(
+        entry_point_error(EntryPoint::Attribute, "", "fn foo(item: Item) {}"),
+        "`#[sin::proc_macro_attribute]` functions take the attribute arguments and the item"
+    );
+    assert_eq!(
+        entry_point_error(EntryPoint::Function, "Foo", "fn foo(input: Ident) {}"),
+        "`#[sin::proc_macro]` does not take arguments"
+    );
+    assert_eq!(
+        entry_point_error(EntryPoint::Derive, "", "fn foo(input: Ident) {}"),
+        "expected the name of the derive, i.e. `#[sin::proc_macro_derive(Name)]`"
+    );
+}
+
+#[test]
+fn test_entry_point_expansion() {
+    let args: TokenStream = "Foo, attributes(foo)".parse().unwrap();
+    let tokens: TokenStream =
+        "/// Docs\npub fn derive_foo(input: Vec<TokenTree>,) -> ParseResult<TokenStream> {}"
+            .parse()
+            .unwrap();
+    let expanded = entry_point(EntryPoint::Derive, &args, &tokens).unwrap();
+    let expected: TokenStream = "
+        #[doc = \" Docs\"]
+        #[proc_macro_derive(Foo, attributes(foo))]
+        pub fn derive_foo(__sin_input: ::proc_macro::TokenStream) -> ::proc_macro::TokenStream {
+            fn derive_foo(input: Vec<TokenTree>,) -> ParseResult<TokenStream> {}
+            ::sin::types::__private::proc_macro(__sin_input, derive_foo)
+        }"
+    .parse()
+    .unwrap();
+    let tokens = |stream: &TokenStream| {
+        stream
+            .iter()
+            .map(sin_types::Token::from)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(tokens(&expanded), tokens(&expected));
+}
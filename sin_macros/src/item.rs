@@ -0,0 +1,426 @@
+//! A minimal parser for the items (structs and enums) that derive macros are applied to, built
+//! directly on top of sin's [`Parser`].
+
+use sin_types::{
+    pat, t, token_stream::Peekable, Delimiter, Group, Ident, Keyword, ParseError, ParseResult,
+    Parser, Punct, Span, Spanned, Token, TokenStream, TokenTree,
+};
+
+pub(crate) enum GenericParamKind {
+    Lifetime,
+    Type,
+    Const,
+}
+
+pub(crate) struct GenericParam {
+    pub kind: GenericParamKind,
+    /// The name of the parameter, i.e. `'a`, `T` or `N`.
+    pub name: TokenTree,
+    /// The declaration of the parameter without its default, i.e. `T: Clone`.
+    pub decl: Vec<TokenTree>,
+}
+
+#[derive(Default)]
+pub(crate) struct Generics {
+    pub params: Vec<GenericParam>,
+    /// The predicates of the `where` clause, without the `where` keyword itself.
+    pub predicates: Vec<TokenTree>,
+}
+
+pub(crate) struct Field {
+    pub args: Vec<SinArg>,
+    pub ident: Option<Ident>,
+    pub ty: Vec<TokenTree>,
+}
+
+pub(crate) enum Fields {
+    Named(Vec<Field>),
+    Unnamed(Vec<Field>),
+    Unit,
+}
+
+/// A single `key` or `key = value` argument of a `#[sin(..)]` attribute.
+pub(crate) struct SinArg {
+    pub key: Ident,
+    pub value: Vec<TokenTree>,
+}
+
+pub(crate) struct Variant {
+    pub args: Vec<SinArg>,
+    pub ident: Ident,
+    pub fields: Fields,
+}
+
+pub(crate) enum Data {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+pub(crate) struct Item {
+    pub ident: Ident,
+    pub generics: Generics,
+    pub data: Data,
+}
+
+type Input<'a> = Parser<'a>;
+
+pub(crate) fn is_punct(tt: Option<&TokenTree>, punct: Punct) -> bool {
+    matches!(tt, Some(TokenTree::Leaf(Token::Punct(p), _)) if *p == punct)
+}
+
+pub(crate) fn is_keyword(tt: Option<&TokenTree>, keyword: Keyword) -> bool {
+    matches!(tt, Some(TokenTree::Leaf(Token::Keyword(k), _)) if *k == keyword)
+}
+
+pub(crate) fn group(tt: Option<&TokenTree>, delimiter: Delimiter) -> Option<Group> {
+    match tt {
+        Some(TokenTree::Tree(group)) if group.delimiter == delimiter => Some(group.clone()),
+        _ => None,
+    }
+}
+
+/// Returns how much `tt` changes the nesting depth of `<...>` brackets.
+fn angle_delta(tt: &TokenTree) -> isize {
+    match tt {
+        TokenTree::Leaf(Token::Punct(Punct::Lt), _) => 1,
+        TokenTree::Leaf(Token::Punct(Punct::Shl), _) => 2,
+        TokenTree::Leaf(Token::Punct(Punct::Gt), _) => -1,
+        TokenTree::Leaf(Token::Punct(Punct::Shr), _) => -2,
+        _ => 0,
+    }
+}
+
+/// Consumes tokens up to (but not including) the next `,` that is not nested within `<...>`.
+pub(crate) fn until_comma(input: &mut Input) -> Vec<TokenTree> {
+    let mut tokens = Vec::new();
+    let mut depth = 0;
+    while let Some(tt) = input.peek() {
+        if depth == 0 && is_punct(Some(&tt), Punct::Comma) {
+            break;
+        }
+        depth += angle_delta(&tt);
+        tokens.push(tt);
+        input.next();
+    }
+    tokens
+}
+
+fn expect(input: &mut Input, expected: Token) -> ParseResult<TokenTree> {
+    match input.next() {
+        Some(tt) if Token::from(tt.clone()) == expected => Ok(tt),
+        Some(tt) => Err(ParseError::new().expected_token(
+            expected.into(),
+            Some(tt.clone().into()),
+            tt.span(),
+        )),
+        None => Err(ParseError::new().expected_token(expected.into(), None, input.span())),
+    }
+}
+
+/// Skips outer attributes such as `#[derive(..)]` and (desugared) doc comments.
+fn skip_attrs(input: &mut Input) {
+    while is_punct(input.peek().as_ref(), Punct::Pound)
+        && group(input.peek_n(2).as_ref(), Delimiter::Bracket).is_some()
+    {
+        input.next();
+        input.next();
+    }
+}
+
+/// Parses the arguments of any `#[sin(..)]` attributes, skipping all other attributes.
+fn parse_attrs(input: &mut Input) -> ParseResult<Vec<SinArg>> {
+    let mut args = Vec::new();
+    while is_punct(input.peek().as_ref(), Punct::Pound) {
+        let Some(attr) = group(input.peek_n(2).as_ref(), Delimiter::Bracket) else {
+            break;
+        };
+        input.next();
+        input.next();
+        let mut attr_input: Input = attr.content.to_parser();
+        let is_sin = matches!(attr_input.next(), Some(TokenTree::Leaf(Token::Ident(ident), _)) if ident == "sin");
+        let Some(content) = group(attr_input.peek().as_ref(), Delimiter::Paren) else {
+            continue;
+        };
+        if !is_sin {
+            continue;
+        }
+        let mut arg_input: Input = content.content.to_parser();
+        while arg_input.peek().is_some() {
+            let key: Ident = arg_input.parse()?;
+            let mut value = Vec::new();
+            if is_punct(arg_input.peek().as_ref(), Punct::Eq) {
+                let eq = arg_input.next().unwrap();
+                value = until_comma(&mut arg_input);
+                if value.is_empty() {
+                    return Err(ParseError::new().error(eq.span(), "expected a value after `=`"));
+                }
+            }
+            args.push(SinArg { key, value });
+            if arg_input.peek().is_some() {
+                expect(&mut arg_input, t![,])?;
+            }
+        }
+    }
+    Ok(args)
+}
+
+fn skip_visibility(input: &mut Input) {
+    if is_keyword(input.peek().as_ref(), Keyword::Pub) {
+        input.next();
+        if group(input.peek().as_ref(), Delimiter::Paren).is_some() {
+            input.next();
+        }
+    }
+}
+
+fn parse_generic_param(tokens: Vec<TokenTree>) -> ParseResult<GenericParam> {
+    let mut depth = 0;
+    let default = tokens.iter().position(|tt| {
+        depth += angle_delta(tt);
+        depth == 0 && is_punct(Some(tt), Punct::Eq)
+    });
+    let decl = tokens[..default.unwrap_or(tokens.len())].to_vec();
+    let (kind, name) = match decl.first().cloned().map(Token::from) {
+        Some(Token::Lifetime(_)) => (GenericParamKind::Lifetime, decl[0].clone()),
+        Some(Token::Keyword(Keyword::Const)) => match decl.get(1) {
+            Some(name) => (GenericParamKind::Const, name.clone()),
+            None => return Err(ParseError::new().error(decl[0].span(), "expected const name")),
+        },
+        Some(Token::Ident(_)) => (GenericParamKind::Type, decl[0].clone()),
+        Some(token) => {
+            return Err(ParseError::new().expected_token(pat![!ident], Some(token), decl[0].span()))
+        }
+        None => unreachable!("empty generic parameters are skipped"),
+    };
+    Ok(GenericParam { kind, name, decl })
+}
+
+fn parse_generics(input: &mut Input) -> ParseResult<Vec<GenericParam>> {
+    if !is_punct(input.peek().as_ref(), Punct::Lt) {
+        return Ok(Vec::new());
+    }
+    input.next();
+    let mut params = Vec::new();
+    let mut param = Vec::new();
+    let mut depth = 1;
+    loop {
+        let Some(tt) = input.next() else {
+            return Err(ParseError::new().expected_token(pat![>], None, input.span()));
+        };
+        depth += angle_delta(&tt);
+        if depth == 0 {
+            break;
+        }
+        if depth == 1 && is_punct(Some(&tt), Punct::Comma) {
+            params.push(parse_generic_param(core::mem::take(&mut param))?);
+            continue;
+        }
+        param.push(tt);
+    }
+    if !param.is_empty() {
+        params.push(parse_generic_param(param)?);
+    }
+    Ok(params)
+}
+
+/// Parses an optional `where` clause, stopping at a brace group, a `;` or the end of input.
+fn parse_where_clause(input: &mut Input) -> Vec<TokenTree> {
+    let mut predicates = Vec::new();
+    if !is_keyword(input.peek().as_ref(), Keyword::Where) {
+        return predicates;
+    }
+    input.next();
+    while let Some(tt) = input.peek() {
+        if group(Some(&tt), Delimiter::Brace).is_some() || is_punct(Some(&tt), Punct::Semi) {
+            break;
+        }
+        predicates.push(tt);
+        input.next();
+    }
+    predicates
+}
+
+fn parse_field_list(content: &TokenStream, named: bool) -> ParseResult<Vec<Field>> {
+    let mut input: Input = content.to_parser();
+    let mut fields = Vec::new();
+    while input.peek().is_some() {
+        let args = parse_attrs(&mut input)?;
+        skip_visibility(&mut input);
+        let ident = match named {
+            true => {
+                let ident: Ident = input.parse()?;
+                expect(&mut input, t![:])?;
+                Some(ident)
+            }
+            false => None,
+        };
+        let ty = until_comma(&mut input);
+        if ty.is_empty() {
+            let found = input.peek();
+            let span = found.as_ref().map(Spanned::span).unwrap_or(input.span());
+            return Err(ParseError::new().error(span, "expected field type"));
+        }
+        fields.push(Field { args, ident, ty });
+        if input.peek().is_some() {
+            expect(&mut input, t![,])?;
+        }
+    }
+    Ok(fields)
+}
+
+/// Parses the fields of a struct following its generics, including any `where` clause.
+fn parse_fields(input: &mut Input, generics: &mut Generics) -> ParseResult<Fields> {
+    generics.predicates = parse_where_clause(input);
+    if let Some(content) = group(input.peek().as_ref(), Delimiter::Brace) {
+        input.next();
+        return Ok(Fields::Named(parse_field_list(&content.content, true)?));
+    }
+    if let Some(content) = group(input.peek().as_ref(), Delimiter::Paren) {
+        input.next();
+        let fields = parse_field_list(&content.content, false)?;
+        generics.predicates = parse_where_clause(input);
+        expect(input, t![;])?;
+        return Ok(Fields::Unnamed(fields));
+    }
+    expect(input, t![;])?;
+    Ok(Fields::Unit)
+}
+
+fn parse_variants(content: &TokenStream) -> ParseResult<Vec<Variant>> {
+    let mut input: Input = content.to_parser();
+    let mut variants = Vec::new();
+    while input.peek().is_some() {
+        let args = parse_attrs(&mut input)?;
+        let ident: Ident = input.parse()?;
+        let fields = match input.peek() {
+            Some(TokenTree::Tree(group)) if group.delimiter == Delimiter::Brace => {
+                input.next();
+                Fields::Named(parse_field_list(&group.content, true)?)
+            }
+            Some(TokenTree::Tree(group)) if group.delimiter == Delimiter::Paren => {
+                input.next();
+                Fields::Unnamed(parse_field_list(&group.content, false)?)
+            }
+            _ => Fields::Unit,
+        };
+        if let Some(eq) = input.peek().filter(|tt| is_punct(Some(tt), Punct::Eq)) {
+            return Err(ParseError::new().error(eq.span(), "discriminants are not supported"));
+        }
+        variants.push(Variant {
+            args,
+            ident,
+            fields,
+        });
+        if input.peek().is_some() {
+            expect(&mut input, t![,])?;
+        }
+    }
+    Ok(variants)
+}
+
+impl Item {
+    pub fn parse(tokens: &TokenStream, derive: &str) -> ParseResult<Item> {
+        let mut input: Input = tokens.to_parser();
+        skip_attrs(&mut input);
+        skip_visibility(&mut input);
+        let is_enum = match input.next() {
+            Some(TokenTree::Leaf(Token::Keyword(Keyword::Struct), _)) => false,
+            Some(TokenTree::Leaf(Token::Keyword(Keyword::Enum), _)) => true,
+            Some(tt) => {
+                return Err(ParseError::new().error(
+                    tt.span(),
+                    format!("`#[derive({derive})]` is only supported on structs and enums"),
+                ))
+            }
+            None => return Err(ParseError::new().error(Span::call_site(), "expected struct")),
+        };
+        let ident: Ident = input.parse()?;
+        let mut generics = Generics {
+            params: parse_generics(&mut input)?,
+            ..Default::default()
+        };
+        let data = match is_enum {
+            true => {
+                generics.predicates = parse_where_clause(&mut input);
+                let Some(content) = group(input.next().as_ref(), Delimiter::Brace) else {
+                    return Err(ParseError::new().error(ident.span, "expected enum variants"));
+                };
+                Data::Enum(parse_variants(&content.content)?)
+            }
+            false => Data::Struct(parse_fields(&mut input, &mut generics)?),
+        };
+        Ok(Item {
+            ident,
+            generics,
+            data,
+        })
+    }
+}
+
+#[test]
+fn test_parse_item_generics() {
+    let tokens: TokenStream =
+        "pub(crate) struct Foo<'a, T: Into<Vec<u8>> = Vec<Vec<u8>>, const N: usize>
+        where T: Clone { a: &'a T, #[sin(paren)] pub b: Rep<T, punct::Comma> }"
+            .parse()
+            .unwrap();
+    let item = Item::parse(&tokens, "Parse").unwrap();
+    assert_eq!(item.ident, "Foo");
+    let params = &item.generics.params;
+    assert_eq!(params.len(), 3);
+    assert!(matches!(params[0].kind, GenericParamKind::Lifetime));
+    assert!(matches!(params[1].kind, GenericParamKind::Type));
+    assert_eq!(params[1].decl.len(), 8);
+    assert!(matches!(params[2].kind, GenericParamKind::Const));
+    assert_eq!(params[2].name.as_str(), "N");
+    assert_eq!(item.generics.predicates.len(), 3);
+    let Data::Struct(Fields::Named(fields)) = &item.data else {
+        panic!("expected named fields");
+    };
+    assert!(fields[0].args.is_empty());
+    assert_eq!(fields[1].args[0].key, "paren");
+    assert_eq!(fields[1].ident.unwrap(), "b");
+    assert_eq!(fields[1].ty.len(), 8);
+}
+
+#[test]
+fn test_parse_item_errors() {
+    let tokens: TokenStream = "union Foo { a: u8 }".parse().unwrap();
+    let err = Item::parse(&tokens, "Parse").err().unwrap();
+    assert_eq!(
+        err.messages[0],
+        "`#[derive(Parse)]` is only supported on structs and enums"
+    );
+    let tokens: TokenStream = "enum Foo { A = 1 }".parse().unwrap();
+    let err = Item::parse(&tokens, "Parse").err().unwrap();
+    assert_eq!(err.messages[0], "discriminants are not supported");
+    let tokens: TokenStream = "struct Foo(u8) where u8: Clone".parse().unwrap();
+    let err = Item::parse(&tokens, "Parse").err().unwrap();
+    assert_eq!(err.messages[0], "expected `;`, found end of tokens");
+}
+
+#[test]
+fn test_parse_item_enum() {
+    let tokens: TokenStream = "enum Foo<T> where T: Clone {
+            /// Docs
+            #[sin(peek = pat![fn], other)]
+            A(T, Ident),
+            #[allow(unused)]
+            B { a: T },
+            C,
+        }"
+    .parse()
+    .unwrap();
+    let item = Item::parse(&tokens, "Parse").unwrap();
+    let Data::Enum(variants) = &item.data else {
+        panic!("expected enum");
+    };
+    assert_eq!(variants.len(), 3);
+    assert_eq!(variants[0].args.len(), 2);
+    assert_eq!(variants[0].args[0].key, "peek");
+    assert_eq!(variants[0].args[0].value.len(), 3);
+    assert!(variants[0].args[1].value.is_empty());
+    assert!(matches!(variants[1].fields, Fields::Named(_)));
+    assert!(variants[1].args.is_empty());
+    assert!(matches!(variants[2].fields, Fields::Unit));
+}
@@ -1,10 +1,210 @@
-// use proc_macro::{TokenStream, TokenTree};
+extern crate proc_macro;
+use entry_point::EntryPoint;
+use proc_macro::TokenStream as TokenStream1;
+use sin_types::{Punct, Token, TokenStream, TokenTree};
 
-// fn escape_quote<S: AsRef<str>>(text: S) -> String {
-//     text.as_ref().replace("\"", "\\\"")
-// }
+mod codegen;
+mod derive_parse;
+mod entry_point;
+mod item;
+mod quoting;
 
-// fn compile_error<S: AsRef<str>>(message: S) -> TokenStream {
-//     let message = escape_quote(message);
-//     format!("compile_error!(\"{}\")", message).parse().unwrap()
-// }
+/// Builds a [`sin_types::TokenStream`] from the provided tokens, similar to `quote::quote!`.
+///
+/// Any [`ToTokens`](sin_types::ToTokens) value can be interpolated using `#var`, and
+/// `#(...)*` / `#(...) sep *` repeat the enclosed tokens once for each item yielded by the
+/// iterators (or collections) interpolated within them, optionally separated by `sep`.
+/// Iterators are cloned rather than consumed, so they must implement [`Clone`] and can be used
+/// in several repetitions. Slices, arrays, [`Vec`]s and [`BTreeSet`]s (or references to them)
+/// are iterated by reference within a repetition, even though a [`Vec`] is also a `ToTokens`
+/// that is interpolated whole outside of one. Values that are neither, like `#name`, are
+/// repeated as-is on every iteration.
+///
+/// Delimiters in the input become proper [`Group`](sin_types::Group)s in the output.
+///
+/// Generated tokens use [`Span::call_site()`](sin_types::Span::call_site), while
+/// interpolated tokens keep their own spans.
+///
+/// ```ignore
+/// let name = Ident::new("Foo");
+/// let fields = vec![Ident::new("a"), Ident::new("b")];
+/// let tokens = quote! {
+///     struct #name {
+///         #(#fields: u8),*
+///     }
+/// };
+/// ```
+///
+/// [`BTreeSet`]: std::collections::BTreeSet
+#[proc_macro]
+pub fn quote(tokens: TokenStream1) -> TokenStream1 {
+    let span = "__sin::Span::call_site()".parse().unwrap();
+    match TokenStream::try_from_proc_macro(tokens) {
+        Ok(tokens) => quoting::quote(tokens, span).into(),
+        Err(err) => err.to_compile_error_proc_macro(),
+    }
+}
+
+/// Like [`quote!`], but applies the [`Span`](sin_types::Span) produced by the expression
+/// before the `=>` to every generated leaf and group, so that errors in the generated code
+/// point at that span rather than at the macro call site.
+///
+/// Passing [`Span::mixed_site()`](sin_types::Span::mixed_site) gives the generated tokens
+/// `macro_rules!`-style hygiene. Interpolated tokens keep their own spans.
+///
+/// ```ignore
+/// let tokens = quote_spanned!(ident.span() => let #ident: u8 = 0;);
+/// ```
+#[proc_macro]
+pub fn quote_spanned(tokens: TokenStream1) -> TokenStream1 {
+    let tokens = match TokenStream::try_from_proc_macro(tokens) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error_proc_macro(),
+    };
+    let is_arrow = |tt: &TokenTree| matches!(tt, TokenTree::Leaf(Token::Punct(Punct::FatArrow), _));
+    let Some(arrow) = tokens.as_slice().iter().position(is_arrow) else {
+        return "::core::compile_error!(\"expected `quote_spanned!(span => tokens)`\");"
+            .parse()
+            .unwrap();
+    };
+    let mut span = "::core::convert::Into::<__sin::Span>::into"
+        .parse::<TokenStream>()
+        .unwrap();
+    span.push(sin_types::Group::new(
+        sin_types::Delimiter::Paren,
+        TokenStream::from_tokens(&tokens.as_slice()[..arrow]),
+    ));
+    let input = TokenStream::from_tokens(&tokens.as_slice()[(arrow + 1)..]);
+    quoting::quote(input, span).into()
+}
+
+/// Derives [`Parse`](sin_types::Parse), [`ToTokens`](sin_types::ToTokens) and
+/// [`Spanned`](sin_types::Spanned) for a struct or enum, turning it into a grammar rule.
+///
+/// Structs are parsed by parsing each of their fields in declaration order via
+/// `Parser::parse::<FieldType>()`. The generated [`ToTokens`](sin_types::ToTokens) impl
+/// concatenates the token streams of the fields, and the generated
+/// [`Spanned`](sin_types::Spanned) impl joins their spans.
+///
+/// Enums are parsed as an ordered choice: each variant is tried in declaration order on a
+/// fork of the input, and the first variant that parses successfully is committed to. A
+/// variant annotated with `#[sin(peek = pat![..])]` is instead committed to as soon as the
+/// next token matches the specified pattern, so errors within it are reported directly.
+/// Similarly, a variant commits once one of its fields parses a `Cut`. If no variant matches,
+/// the expected patterns of all of the variants are merged into a single error such as
+/// "expected `fn`, `struct` or ident, found `,`". Types without generic type or lifetime
+/// parameters are memoized by memoizing parsers, such as the one used by `parse` (see
+/// `Parser::memoize`), so their variants may be directly left-recursive, i.e.
+/// `Field(Box<Expr>, punct::Dot, Ident)` in `enum Expr`.
+///
+/// Fields can be annotated with the following attributes to control how they are parsed:
+///
+/// - `#[sin(paren)]`, `#[sin(brace)]` or `#[sin(bracket)]`: the field is parsed from the
+///   content of a group with that delimiter, which must be consumed entirely. The generated
+///   `ToTokens` impl wraps the field in a new group with that delimiter, spanning the field.
+/// - `#[sin(until = pat![;])]`: the field is parsed from every token up to (but not including)
+///   the next one matching the pattern, all of which must be consumed.
+/// - `#[sin(terminated)]`: the field must consume all of the remaining input.
+/// - `#[sin(optional_if = pat![..])]`: the field is an `Option` that is only parsed if the next
+///   token matches the pattern. This can be combined with any of the above.
+///
+/// Since [`Parse`](sin_types::Parse) requires them, the type must also implement `Clone`,
+/// `Eq`, `Ord`, `Hash` and `Debug`. A `Parse` bound is added for every type parameter.
+///
+/// ```ignore
+/// #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+/// struct Field {
+///     name: Ident,
+///     colon: punct::Colon,
+///     ty: Ident,
+/// }
+///
+/// #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+/// struct Call {
+///     name: Ident,
+///     #[sin(paren)]
+///     args: Rep<Field, punct::Comma>,
+///     #[sin(optional_if = pat![;])]
+///     semi: Option<punct::Semi>,
+/// }
+///
+/// #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+/// enum Arg {
+///     #[sin(peek = pat![&])]
+///     Ref(punct::And, Ident),
+///     Typed(Field),
+///     Name(Ident),
+/// }
+/// ```
+#[proc_macro_derive(Parse, attributes(sin))]
+pub fn derive_parse(tokens: TokenStream1) -> TokenStream1 {
+    match TokenStream::try_from_proc_macro(tokens)
+        .and_then(|tokens| derive_parse::derive_parse(&tokens))
+    {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error_proc_macro(),
+    }
+}
+
+/// Turns a function taking a parsed grammar into a function-like proc macro, i.e.
+/// `my_macro!(..)`. The input is parsed entirely as the argument of the function, and the
+/// tokens it returns become the output of the macro.
+///
+/// Parse errors, including those returned by the function, are reported as spanned
+/// `compile_error!`s, and a panic within the function is reported as a `compile_error!` at
+/// the call site.
+///
+/// ```ignore
+/// #[sin::proc_macro]
+/// pub fn my_macro(input: MyGrammar) -> ParseResult<impl ToTokens> {
+///     Ok(quote!(..))
+/// }
+/// ```
+///
+/// Re-exported by `sin` as `sin::proc_macro`. Since the generated function carries the built-in
+/// `#[proc_macro]` attribute, which a glob import of `sin::*` makes ambiguous, proc macro
+/// crates should refer to this attribute by its path and import other items explicitly.
+#[proc_macro_attribute]
+pub fn sin_proc_macro(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
+    expand_entry_point(EntryPoint::Function, attr, item)
+}
+
+/// Like [`macro@sin_proc_macro`], but for attribute macros, i.e. `#[my_attribute(..)]`. The
+/// function takes the arguments of the attribute followed by the item it is applied to, each
+/// parsed entirely. Re-exported by `sin` as `sin::proc_macro_attribute`.
+///
+/// ```ignore
+/// #[sin::proc_macro_attribute]
+/// pub fn my_attribute(args: Rep<Ident>, item: Vec<TokenTree>) -> ParseResult<impl ToTokens> {
+///     Ok(quote!(..))
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn sin_proc_macro_attribute(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
+    expand_entry_point(EntryPoint::Attribute, attr, item)
+}
+
+/// Like [`macro@sin_proc_macro`], but for derive macros. Takes the same arguments as the
+/// built-in `#[proc_macro_derive]` attribute. Re-exported by `sin` as `sin::proc_macro_derive`.
+///
+/// ```ignore
+/// #[sin::proc_macro_derive(MyTrait, attributes(my_trait))]
+/// pub fn derive_my_trait(item: MyItem) -> ParseResult<impl ToTokens> {
+///     Ok(quote!(..))
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn sin_proc_macro_derive(attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
+    expand_entry_point(EntryPoint::Derive, attr, item)
+}
+
+fn expand_entry_point(kind: EntryPoint, attr: TokenStream1, item: TokenStream1) -> TokenStream1 {
+    let expanded = TokenStream::try_from_proc_macro(attr).and_then(|attr| {
+        let item = TokenStream::try_from_proc_macro(item)?;
+        entry_point::entry_point(kind, &attr, &item)
+    });
+    match expanded {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error_proc_macro(),
+    }
+}
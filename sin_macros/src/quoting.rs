@@ -0,0 +1,236 @@
+use crate::codegen::{block, code, gen, paren};
+use sin_types::{Delimiter, Group, Literal, Punct, Token, TokenStream, TokenTree};
+
+enum Fragment<'a> {
+    Tokens(&'a [TokenTree]),
+    Interpolation(&'a TokenTree),
+    Group(&'a Group),
+    Repetition {
+        body: &'a Group,
+        separator: Option<&'a TokenTree>,
+    },
+}
+
+fn is_pound(tt: &TokenTree) -> bool {
+    matches!(tt, TokenTree::Leaf(Token::Punct(Punct::Pound), _))
+}
+
+fn is_star(tt: Option<&TokenTree>) -> bool {
+    matches!(tt, Some(TokenTree::Leaf(Token::Punct(Punct::Star), _)))
+}
+
+/// Splits quoted input into runs of plain tokens, `#var` interpolations, groups and
+/// `#(...)*` / `#(...) sep *` repetitions.
+fn fragments(tokens: &[TokenTree]) -> Vec<Fragment<'_>> {
+    let mut fragments = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let (fragment, len) = match (&tokens[i], tokens.get(i + 1)) {
+            (pound, Some(var @ TokenTree::Leaf(Token::Ident(_), _))) if is_pound(pound) => {
+                (Fragment::Interpolation(var), 2)
+            }
+            (pound, Some(TokenTree::Tree(body)))
+                if is_pound(pound) && body.delimiter == Delimiter::Paren =>
+            {
+                match tokens.get(i + 2) {
+                    star if is_star(star) => (
+                        Fragment::Repetition {
+                            body,
+                            separator: None,
+                        },
+                        3,
+                    ),
+                    Some(separator @ TokenTree::Leaf(..)) if is_star(tokens.get(i + 3)) => (
+                        Fragment::Repetition {
+                            body,
+                            separator: Some(separator),
+                        },
+                        4,
+                    ),
+                    _ => {
+                        i += 1;
+                        continue;
+                    }
+                }
+            }
+            (TokenTree::Tree(group), _) => (Fragment::Group(group), 1),
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        if start < i {
+            fragments.push(Fragment::Tokens(&tokens[start..i]));
+        }
+        fragments.push(fragment);
+        i += len;
+        start = i;
+    }
+    if start < tokens.len() {
+        fragments.push(Fragment::Tokens(&tokens[start..]));
+    }
+    fragments
+}
+
+/// Collects the (deduplicated) `#var` interpolations appearing anywhere within `tokens`.
+fn interpolated_vars<'a>(tokens: &'a [TokenTree], vars: &mut Vec<&'a TokenTree>) {
+    for fragment in fragments(tokens) {
+        match fragment {
+            Fragment::Tokens(_) => (),
+            Fragment::Interpolation(var) => {
+                if !vars.iter().any(|v| v.as_str() == var.as_str()) {
+                    vars.push(var);
+                }
+            }
+            Fragment::Group(group) | Fragment::Repetition { body: group, .. } => {
+                interpolated_vars(group.content.as_slice(), vars)
+            }
+        }
+    }
+}
+
+/// Generates an expression constructing `token`, where `__sin` is the path of `sin_types`.
+fn token_expr(token: &Token) -> String {
+    let text = token.as_str();
+    match token {
+        Token::Ident(_) => format!("__sin::Token::Ident(__sin::InStr::from({text:?}))"),
+        Token::Keyword(keyword) => format!("__sin::Token::Keyword(__sin::Keyword::{keyword:?})"),
+        Token::CustomKeyword(_) => {
+            format!("__sin::Token::CustomKeyword(__sin::InStr::from({text:?}))")
+        }
+        Token::Lifetime(_) => format!("__sin::Token::Lifetime(__sin::InStr::from({text:?}))"),
+        Token::Punct(punct) => format!("__sin::Token::Punct(__sin::Punct::{punct:?})"),
+        Token::Delimiter(delimiter) => {
+            format!("__sin::Token::Delimiter(__sin::Delimiter::{delimiter:?})")
+        }
+        Token::Literal(Literal::Bool(value)) => {
+            format!("__sin::Token::Literal(__sin::Literal::Bool({value}))")
+        }
+        Token::Literal(Literal::Char(c)) => {
+            format!("__sin::Token::Literal(__sin::Literal::Char({c:?}))")
+        }
+        Token::Literal(Literal::String(string)) => {
+            let string = string.as_str();
+            format!("__sin::Token::Literal(__sin::Literal::String(__sin::InStr::from({string:?})))")
+        }
+        // numbers and byte (string) literals keep their source text, which litrs parses
+        Token::Literal(_) => format!(
+            "__sin::Token::Literal(<__sin::Literal as __sin::ParseLiteral>::parse({text:?})
+                .expect(\"quoted literals are valid\"))"
+        ),
+        other => panic!("cannot quote `{other}`"),
+    }
+}
+
+/// Generates a statement appending `leaf` to `__sin_tokens` with the span in `__sin_span`.
+fn push_leaf(leaf: &TokenTree) -> TokenStream {
+    let token = Token::from(leaf.clone());
+    code(&format!(
+        "__sin_tokens.push(__sin::TokenTree::Leaf({}, __sin_span));",
+        token_expr(&token)
+    ))
+}
+
+/// Generates an expression that evaluates to a [`TokenStream`] containing `tokens`, with
+/// every generated leaf and group using the span stored in `__sin_span`.
+fn expand(tokens: &[TokenTree]) -> TokenStream {
+    let mut stmts = code("let mut __sin_tokens = __sin::TokenStream::new();");
+    for fragment in fragments(tokens) {
+        match fragment {
+            Fragment::Tokens(tokens) => {
+                for leaf in tokens {
+                    stmts.extend(push_leaf(leaf));
+                }
+            }
+            Fragment::Interpolation(var) => {
+                stmts.push(var);
+                stmts.extend(code(".sin_interpolate(&mut __sin_tokens);"));
+            }
+            Fragment::Group(group) => {
+                let mut args = code(&format!(
+                    "__sin_span, __sin::Delimiter::{:?},",
+                    group.delimiter
+                ));
+                args.push(block(expand(group.content.as_slice())));
+                let group = gen!("__sin::Group::new_spanned", paren(args));
+                stmts.extend(gen!("__sin_tokens.push", paren(group), ";"));
+            }
+            Fragment::Repetition { body, separator } => {
+                stmts.push(block(expand_repetition(body, separator)));
+            }
+        }
+    }
+    stmts.extend(code("__sin_tokens"));
+    stmts
+}
+
+fn expand_repetition(body: &Group, separator: Option<&TokenTree>) -> TokenStream {
+    let mut vars = Vec::new();
+    interpolated_vars(body.content.as_slice(), &mut vars);
+    let mut stmts = code("let __sin_has_iter = __sin::__private::ThereIsNoIteratorInRepetition;");
+    for var in &vars {
+        let mut pattern = code("mut");
+        pattern.push(*var);
+        pattern.extend(code(", __sin_i"));
+        stmts.extend(code("#[allow(unused_mut)] let"));
+        stmts.push(Group::new(Delimiter::Paren, pattern));
+        // see `RepWrap` for how the receiver determines what the variable is treated as
+        let mut reference = code("&");
+        reference.push(*var);
+        let mut receiver = code("&& __sin::__private::RepWrap");
+        receiver.push(Group::new(Delimiter::Paren, reference));
+        stmts.extend(code("="));
+        stmts.push(Group::new(Delimiter::Paren, receiver));
+        stmts.extend(code(
+            ".sin_into_iter(); let __sin_has_iter = __sin_has_iter | __sin_i;",
+        ));
+    }
+    stmts.extend(code(
+        "let _: __sin::__private::HasIterator = __sin_has_iter;
+        let mut __sin_first = true;",
+    ));
+    let mut body_stmts = TokenStream::new();
+    for var in &vars {
+        body_stmts.extend(code("let"));
+        body_stmts.push(*var);
+        body_stmts.extend(code("= match"));
+        body_stmts.push(*var);
+        body_stmts.extend(code(
+            ".next() { Some(__sin_next) => __sin_next, None => break };",
+        ));
+    }
+    if let Some(separator) = separator {
+        body_stmts.extend(code("if !__sin_first"));
+        body_stmts.push(block(push_leaf(separator)));
+    }
+    body_stmts.extend(code("__sin_first = false; __sin_tokens.extend"));
+    body_stmts.push(Group::new(
+        Delimiter::Paren,
+        TokenStream::from_tokens(&[block(expand(body.content.as_slice()))]),
+    ));
+    body_stmts.extend(code(";"));
+    stmts.extend(code("loop"));
+    stmts.push(block(body_stmts));
+    stmts
+}
+
+/// Expands the input of a `quote!`-style macro into an expression producing a sin
+/// [`TokenStream`], where `span` is an expression evaluating to the [`sin_types::Span`]
+/// applied to every generated leaf and group. The expression may refer to `sin_types` as
+/// `__sin`, which is the only place the path of the crate is spelled out.
+pub fn quote(input: TokenStream, span: TokenStream) -> TokenStream {
+    let mut stmts = code(
+        "#[allow(unused_imports)]
+        use ::sin::types as __sin;
+        #[allow(unused_imports)]
+        use __sin::__private::{
+            Interpolate as _, RepCollectionExt as _, RepIteratorExt as _, RepToTokensExt as _,
+        };
+        let __sin_span: __sin::Span =",
+    );
+    stmts.extend(span);
+    stmts.extend(code(";"));
+    stmts.push(block(expand(input.as_slice())));
+    TokenStream::from_tokens(&[block(stmts)])
+}
@@ -0,0 +1,344 @@
+//! Runtime support for the code generated by the `quote!` family of macros and the
+//! `#[sin::proc_macro]` family of attributes in `sin_macros`.
+//!
+//! Nothing in here is considered public API.
+
+use crate::*;
+use core::{iter::Repeat, ops::BitOr};
+use proc_macro::TokenStream as TokenStream1;
+use std::{
+    collections::{btree_set, BTreeSet},
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+pub use litrs::Literal;
+
+/// Appends a [`Group`] with the specified [`Delimiter`], `content` and [`Span`] to `tokens`.
+pub fn push_group(
+    tokens: &mut TokenStream,
+    delimiter: Delimiter,
+    content: TokenStream,
+    span: Span,
+) {
+    tokens.push(Group::new_spanned(span, delimiter, content));
+}
+
+/// Joins the specified [`Span`]s into a single [`Span`] covering all of them, ignoring
+/// call-site spans (i.e. those of empty fields) as well as any spans that cannot be joined.
+pub fn join_spans(spans: &[Span]) -> Span {
+    let mut spans = spans.iter().filter(|span| !span.is_call_site());
+    let Some(first) = spans.next() else {
+        return Span::call_site();
+    };
+    spans.fold(*first, |joined, span| joined.join(*span).unwrap_or(joined))
+}
+
+/// Used to interpolate `#var` into a [`TokenStream`] via method call syntax so that
+/// references to [`ToTokens`] values are auto-dereferenced.
+pub trait Interpolate {
+    fn sin_interpolate(&self, tokens: &mut TokenStream);
+}
+
+impl<T: ToTokens> Interpolate for T {
+    fn sin_interpolate(&self, tokens: &mut TokenStream) {
+        tokens.extend(self.to_token_stream());
+    }
+}
+
+/// Marker indicating that a `#var` within a repetition is being iterated over.
+#[derive(Copy, Clone)]
+pub struct HasIterator;
+
+/// Marker indicating that a `#var` within a repetition is repeated as-is.
+#[derive(Copy, Clone)]
+pub struct ThereIsNoIteratorInRepetition;
+
+impl BitOr<ThereIsNoIteratorInRepetition> for ThereIsNoIteratorInRepetition {
+    type Output = ThereIsNoIteratorInRepetition;
+
+    fn bitor(self, _rhs: ThereIsNoIteratorInRepetition) -> Self::Output {
+        ThereIsNoIteratorInRepetition
+    }
+}
+
+impl BitOr<ThereIsNoIteratorInRepetition> for HasIterator {
+    type Output = HasIterator;
+
+    fn bitor(self, _rhs: ThereIsNoIteratorInRepetition) -> Self::Output {
+        HasIterator
+    }
+}
+
+impl BitOr<HasIterator> for ThereIsNoIteratorInRepetition {
+    type Output = HasIterator;
+
+    fn bitor(self, _rhs: HasIterator) -> Self::Output {
+        HasIterator
+    }
+}
+
+impl BitOr<HasIterator> for HasIterator {
+    type Output = HasIterator;
+
+    fn bitor(self, _rhs: HasIterator) -> Self::Output {
+        HasIterator
+    }
+}
+
+/// Wraps a `#var` interpolated within a `#(...)*` repetition. The repetition calls
+/// `(&&RepWrap(&var)).sin_into_iter()`, which resolves (in order of priority) to
+/// [`RepIteratorExt`] for iterators, [`RepCollectionExt`] for collections and
+/// [`RepToTokensExt`] for everything else, since each is implemented one reference further
+/// away from the receiver.
+pub struct RepWrap<'q, T: ?Sized>(pub &'q T);
+
+impl<T: ?Sized> Clone for RepWrap<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for RepWrap<'_, T> {}
+
+/// Allows iterators to be used directly within a `#(...)*` repetition. The iterator is
+/// cloned, so the same iterator can be used in several repetitions.
+pub trait RepIteratorExt {
+    type Iter: Iterator;
+
+    fn sin_into_iter(self) -> (Self::Iter, HasIterator);
+}
+
+impl<I: Iterator + Clone> RepIteratorExt for &&RepWrap<'_, I> {
+    type Iter = I;
+
+    fn sin_into_iter(self) -> (Self::Iter, HasIterator) {
+        (self.0.clone(), HasIterator)
+    }
+}
+
+/// Allows [`RepAsIteratorExt`] collections to be iterated by reference within a `#(...)*`
+/// repetition, even if they also implement [`ToTokens`] (i.e. [`Vec`]).
+pub trait RepCollectionExt<'q> {
+    type Iter: Iterator;
+
+    fn sin_into_iter(self) -> (Self::Iter, HasIterator);
+}
+
+impl<'q, T: RepAsIteratorExt<'q> + ?Sized> RepCollectionExt<'q> for &RepWrap<'q, T> {
+    type Iter = T::Iter;
+
+    fn sin_into_iter(self) -> (Self::Iter, HasIterator) {
+        RepAsIteratorExt::sin_into_iter(self.0)
+    }
+}
+
+/// Collections that can be iterated by reference within a `#(...)*` repetition.
+pub trait RepAsIteratorExt<'q> {
+    type Iter: Iterator;
+
+    fn sin_into_iter(&'q self) -> (Self::Iter, HasIterator);
+}
+
+impl<'q, T: 'q> RepAsIteratorExt<'q> for [T] {
+    type Iter = core::slice::Iter<'q, T>;
+
+    fn sin_into_iter(&'q self) -> (Self::Iter, HasIterator) {
+        (self.iter(), HasIterator)
+    }
+}
+
+impl<'q, T: 'q, const N: usize> RepAsIteratorExt<'q> for [T; N] {
+    type Iter = core::slice::Iter<'q, T>;
+
+    fn sin_into_iter(&'q self) -> (Self::Iter, HasIterator) {
+        (self.iter(), HasIterator)
+    }
+}
+
+impl<'q, T: 'q> RepAsIteratorExt<'q> for Vec<T> {
+    type Iter = core::slice::Iter<'q, T>;
+
+    fn sin_into_iter(&'q self) -> (Self::Iter, HasIterator) {
+        (self.iter(), HasIterator)
+    }
+}
+
+impl<'q, T: 'q> RepAsIteratorExt<'q> for BTreeSet<T> {
+    type Iter = btree_set::Iter<'q, T>;
+
+    fn sin_into_iter(&'q self) -> (Self::Iter, HasIterator) {
+        (self.iter(), HasIterator)
+    }
+}
+
+impl<'q, T: RepAsIteratorExt<'q> + ?Sized> RepAsIteratorExt<'q> for &T {
+    type Iter = T::Iter;
+
+    fn sin_into_iter(&'q self) -> (Self::Iter, HasIterator) {
+        <T as RepAsIteratorExt>::sin_into_iter(*self)
+    }
+}
+
+/// Allows any other value (typically a [`ToTokens`]) to be repeated as-is within a `#(...)*`
+/// repetition, provided some other `#var` in the repetition is iterated over.
+pub trait RepToTokensExt<'q> {
+    type Item: ?Sized;
+
+    fn sin_into_iter(self) -> (Repeat<&'q Self::Item>, ThereIsNoIteratorInRepetition);
+}
+
+impl<'q, T: ?Sized> RepToTokensExt<'q> for RepWrap<'q, T> {
+    type Item = T;
+
+    fn sin_into_iter(self) -> (Repeat<&'q T>, ThereIsNoIteratorInRepetition) {
+        (core::iter::repeat(self.0), ThereIsNoIteratorInRepetition)
+    }
+}
+
+/// Runs the body of a proc macro, turning its [`ParseError`]s into `compile_error!`s and a
+/// panic into a `compile_error!` at the call site, so that a bug in a macro is reported like
+/// any other error rather than as a compiler crash.
+pub fn expand<R: ToTokens>(body: impl FnOnce() -> ParseResult<R>) -> TokenStream {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(output)) => output.to_token_stream(),
+        Ok(Err(error)) => error.to_compile_error(),
+        Err(payload) => {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => match payload.downcast_ref::<String>() {
+                    Some(message) => message.clone(),
+                    None => "unknown panic payload".to_string(),
+                },
+            };
+            let message = format!("proc macro panicked: {message}");
+            ParseError::new()
+                .error(Span::call_site(), message)
+                .to_compile_error()
+        }
+    }
+}
+
+/// Expands a `#[sin::proc_macro]` or `#[sin::proc_macro_derive]` function, parsing all of
+/// `input` as its argument.
+pub fn proc_macro<P: Parse, R: ToTokens>(
+    input: TokenStream1,
+    body: impl FnOnce(P) -> ParseResult<R>,
+) -> TokenStream1 {
+    expand_parsed(TokenStream::try_from_proc_macro(input), body).into()
+}
+
+/// Parses `input` and passes it to `body`. Errors recovered from while parsing don't prevent
+/// the expansion, they are reported along with its output, see [`with_recovered`].
+fn expand_parsed<P: Parse, R: ToTokens>(
+    input: ParseResult<TokenStream>,
+    body: impl FnOnce(P) -> ParseResult<R>,
+) -> TokenStream {
+    expand(|| {
+        let (parsed, errors) = P::parse_tokens_recovering(input?)?;
+        with_recovered(errors, body(parsed))
+    })
+}
+
+/// Expands a `#[sin::proc_macro_attribute]` function, parsing all of `attr` and `item` as its
+/// arguments and reporting the errors of both if either fails to parse.
+pub fn proc_macro_attribute<A: Parse, I: Parse, R: ToTokens>(
+    attr: TokenStream1,
+    item: TokenStream1,
+    body: impl FnOnce(A, I) -> ParseResult<R>,
+) -> TokenStream1 {
+    expand(|| {
+        let mut errors = ParseError::new();
+        let attr = TokenStream::try_from_proc_macro(attr)
+            .and_then(|attr| A::parse_tokens_recovering(attr));
+        let attr = recovered(attr, &mut errors);
+        let item = TokenStream::try_from_proc_macro(item)
+            .and_then(|item| I::parse_tokens_recovering(item));
+        let item = recovered(item, &mut errors);
+        match (attr, item) {
+            (Some(attr), Some(item)) => with_recovered(errors, body(attr, item)),
+            _ => Err(errors),
+        }
+    })
+    .into()
+}
+
+/// Returns the parsed value of `result`, moving its recovered errors (or its error) to
+/// `errors`.
+fn recovered<P>(result: ParseResult<(P, ParseError)>, errors: &mut ParseError) -> Option<P> {
+    match result {
+        Ok((parsed, recovered)) => {
+            errors.messages.extend(recovered.messages);
+            Some(parsed)
+        }
+        Err(error) => {
+            errors.messages.extend(error.messages);
+            None
+        }
+    }
+}
+
+/// Appends the `compile_error!`s of `errors` to `output`, or prepends `errors` to its error.
+fn with_recovered<R: ToTokens>(
+    mut errors: ParseError,
+    output: ParseResult<R>,
+) -> ParseResult<TokenStream> {
+    match output {
+        Ok(output) => {
+            let mut tokens = output.to_token_stream();
+            tokens.extend(errors.to_compile_error());
+            Ok(tokens)
+        }
+        Err(error) => {
+            errors.messages.extend(error.messages);
+            Err(errors)
+        }
+    }
+}
+
+#[test]
+fn test_expand() {
+    let tokens: TokenStream = "a b".parse().unwrap();
+    let output = expand(|| parse::<Vec<Ident>>(&tokens));
+    assert_eq!(output.len(), 2);
+    let output = expand(|| parse::<Ident>(&tokens));
+    assert_eq!(
+        output
+            .iter()
+            .filter(|tt| tt.as_str() == "compile_error")
+            .count(),
+        1
+    );
+    let output = expand::<TokenStream>(|| panic!("oops {}", 1));
+    let Some(TokenTree::Tree(group)) = output.iter().nth(5) else {
+        panic!("expected a compile_error! invocation");
+    };
+    let message = group.content.iter().next().unwrap();
+    assert_eq!(message.as_str(), "proc macro panicked: oops 1");
+}
+
+#[test]
+fn test_expand_recovered() {
+    type Statement = Recover<(Ident, punct::Eq, Ident, punct::Semi), punct::Semi>;
+    let compile_errors = |tokens: &TokenStream| {
+        tokens
+            .iter()
+            .filter(|tt| tt.as_str() == "compile_error")
+            .count()
+    };
+    let tokens: TokenStream = "a = b; c = ; d = e;".parse().unwrap();
+    // the statements that parsed are still expanded, along with the error of the other one
+    let output = expand_parsed(Ok(tokens.clone()), |statements: Vec<Statement>| {
+        let names: Vec<Ident> = statements
+            .iter()
+            .filter_map(|statement| statement.parsed().map(|parsed| parsed.0))
+            .collect();
+        Ok(names)
+    });
+    assert_eq!(output.iter().next().unwrap().as_str(), "a");
+    assert_eq!(output.iter().nth(1).unwrap().as_str(), "d");
+    assert_eq!(compile_errors(&output), 1);
+    let output = expand_parsed(Ok(tokens), |_: Vec<Statement>| -> ParseResult<Ident> {
+        Err(ParseError::new().error(Span::call_site(), "unsupported"))
+    });
+    assert_eq!(compile_errors(&output), 2);
+}
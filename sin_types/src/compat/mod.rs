@@ -0,0 +1,18 @@
+//! Feature-gated interoperability with the `proc-macro2` / `syn` ecosystem.
+//!
+//! With the `proc-macro2` feature enabled, [`TokenStream`](crate::TokenStream),
+//! [`TokenTree`](crate::TokenTree), [`Group`](crate::Group), [`Ident`](crate::Ident),
+//! [`Literal`](crate::Literal) and [`Span`](crate::Span) can be converted to and from their
+//! `proc_macro2` equivalents. These conversions also work outside of a proc macro context, in
+//! which case sin uses fallback [`Span`](crate::Span)s.
+//!
+//! The `syn` feature additionally provides `Syn`, which allows any `syn` type to be parsed
+//! from within a sin [`Parser`](crate::Parser).
+
+#[cfg(feature = "proc-macro2")]
+mod proc_macro2;
+
+#[cfg(feature = "syn")]
+mod syn;
+#[cfg(feature = "syn")]
+pub use self::syn::*;
@@ -0,0 +1,327 @@
+use crate::{
+    span::{Span1Extensions, SpanData, SpanStyle},
+    *,
+};
+use proc_macro::Span as Span1;
+use proc_macro2::{
+    Delimiter as Delimiter2, Group as Group2, Ident as Ident2, Literal as Literal2,
+    Punct as Punct2, Spacing, Span as Span2, TokenStream as TokenStream2, TokenTree as TokenTree2,
+};
+
+impl From<Span2> for Span {
+    /// Converts a [`proc_macro2::Span`] into a [`Span`].
+    ///
+    /// Inside of a proc macro this preserves the underlying compiler span. Outside of a proc
+    /// macro, the resulting [`Span`] uses the fallback implementation, retaining the source
+    /// text of the original span when `proc_macro2` makes it available.
+    fn from(span: Span2) -> Self {
+        if proc_macro::is_available() {
+            return span.unwrap().into();
+        }
+        match span.source_text() {
+            Some(text) => Span::new(text),
+            None => Span::call_site(),
+        }
+    }
+}
+
+impl From<Span> for Span2 {
+    fn from(span: Span) -> Self {
+        match span.span_data() {
+            SpanData::ProcMacro(id) => unsafe { Span1::from_id(*id) }.into(),
+            SpanData::Fallback { style, .. } => match style {
+                SpanStyle::MixedSite => Span2::mixed_site(),
+                SpanStyle::Normal | SpanStyle::CallSite => Span2::call_site(),
+            },
+        }
+    }
+}
+
+impl From<Ident2> for Ident {
+    fn from(ident: Ident2) -> Self {
+        Ident::new_spanned(ident.span().into(), ident.to_string())
+    }
+}
+
+impl From<Ident> for Ident2 {
+    fn from(ident: Ident) -> Self {
+        word_to_ident2(ident.ident.as_str(), ident.span.into())
+    }
+}
+
+impl TryFrom<Literal2> for Literal {
+    type Error = TokenParseError;
+
+    fn try_from(literal: Literal2) -> Result<Self, Self::Error> {
+        let text = literal.to_string();
+        Literal::parse(&text)
+            .map_err(|err| InStr::from(format!("invalid literal `{text}`: {err}")).into())
+    }
+}
+
+impl TryFrom<Literal> for Literal2 {
+    type Error = TokenParseError;
+
+    /// Converts a [`Literal`] into a [`proc_macro2::Literal`] with a call-site span.
+    ///
+    /// `proc_macro2` represents `true` and `false` as identifiers, so bool [`Literal`]s are
+    /// rejected here and can only be converted as part of a [`TokenTree`] or [`TokenStream`].
+    fn try_from(literal: Literal) -> Result<Self, Self::Error> {
+        match literal {
+            Literal::Bool(_) => Err("bool literals are identifiers in proc_macro2".into()),
+            Literal::Char(c) => Ok(Literal2::character(c)),
+            Literal::String(string) => Ok(Literal2::string(string.as_str())),
+            _ => Ok(literal
+                .in_str()
+                .as_str()
+                .parse()
+                .expect("litrs literals are always valid Rust literals")),
+        }
+    }
+}
+
+impl TryFrom<Group2> for Group {
+    type Error = ParseError;
+
+    /// Converts a [`proc_macro2::Group`] into a [`Group`], reporting any token of its content
+    /// that sin cannot represent as a [`ParseError`] pointing at it.
+    fn try_from(group: Group2) -> Result<Self, Self::Error> {
+        let delimiter = match group.delimiter() {
+            Delimiter2::Parenthesis => Delimiter::Paren,
+            Delimiter2::Brace => Delimiter::Brace,
+            Delimiter2::Bracket => Delimiter::Bracket,
+            Delimiter2::None => {
+                let message = "sin does not support invisible groups";
+                return Err(ParseError::new().error(group.span().into(), message));
+            }
+        };
+        Ok(Group {
+            delimiter,
+            span: group.span().into(),
+            span_open: group.span_open().into(),
+            span_close: group.span_close().into(),
+            content: TokenStream::try_from(group.stream())?,
+        })
+    }
+}
+
+impl From<Group> for Group2 {
+    fn from(group: Group) -> Self {
+        group2(group.delimiter, group.content.into(), group.span)
+    }
+}
+
+impl TryFrom<TokenTree2> for TokenTree {
+    type Error = ParseError;
+
+    /// Converts a single [`proc_macro2::TokenTree`] into a [`TokenTree`].
+    ///
+    /// Since `proc_macro2` punctuation is always a single character, multi-character
+    /// punctuation and lifetimes can only be reassembled by converting a whole
+    /// [`proc_macro2::TokenStream`] instead. A lone `'` is therefore rejected here.
+    fn try_from(tt: TokenTree2) -> Result<Self, Self::Error> {
+        match tt {
+            TokenTree2::Group(group) => Ok(TokenTree::Tree(group.try_into()?)),
+            TokenTree2::Ident(ident) => Ok(TokenTree::Leaf(
+                Token::from_word(ident.to_string()),
+                ident.span().into(),
+            )),
+            TokenTree2::Punct(punct) => {
+                let span = punct.span().into();
+                let chars = punct.as_char().to_string();
+                let punct_token = Punct::try_from(chars.as_str())
+                    .map_err(|err| ParseError::new().error(span, err.msg))?;
+                Ok(TokenTree::Leaf(Token::Punct(punct_token), span))
+            }
+            TokenTree2::Literal(literal) => {
+                let span = literal.span().into();
+                let literal = Literal::try_from(literal)
+                    .map_err(|err| ParseError::new().error(span, err.msg))?;
+                Ok(TokenTree::Leaf(Token::Literal(literal), span))
+            }
+        }
+    }
+}
+
+impl From<TokenTree> for TokenStream2 {
+    /// Converts a [`TokenTree`] into the (possibly several) [`proc_macro2::TokenTree`]s
+    /// representing it, since multi-character punctuation and lifetimes have no single
+    /// `proc_macro2` equivalent.
+    fn from(tt: TokenTree) -> Self {
+        TokenStream::from_tokens(&[tt]).into_foreign()
+    }
+}
+
+impl ForeignTokenStream for TokenStream2 {
+    fn read(tt: TokenTree2) -> ForeignToken<Self> {
+        match tt {
+            TokenTree2::Group(group) => ForeignToken::Group {
+                delimiter: match group.delimiter() {
+                    Delimiter2::Parenthesis => Some(Delimiter::Paren),
+                    Delimiter2::Brace => Some(Delimiter::Brace),
+                    Delimiter2::Bracket => Some(Delimiter::Bracket),
+                    Delimiter2::None => None,
+                },
+                span: group.span().into(),
+                span_open: group.span_open().into(),
+                span_close: group.span_close().into(),
+                stream: group.stream(),
+            },
+            TokenTree2::Ident(ident) => ForeignToken::Ident(ident.to_string(), ident.span().into()),
+            TokenTree2::Punct(punct) => ForeignToken::Punct {
+                ch: punct.as_char(),
+                joint: punct.spacing() == Spacing::Joint,
+                span: punct.span().into(),
+            },
+            TokenTree2::Literal(literal) => {
+                ForeignToken::Literal(literal.to_string(), literal.span().into())
+            }
+        }
+    }
+
+    fn group(delimiter: Delimiter, content: Self, span: Span) -> TokenTree2 {
+        group2(delimiter, content, span).into()
+    }
+
+    fn ident(name: &str, raw: bool, span: Span) -> TokenTree2 {
+        match raw {
+            true => Ident2::new_raw(name, span.into()).into(),
+            false => Ident2::new(name, span.into()).into(),
+        }
+    }
+
+    fn punct(ch: char, joint: bool, span: Span) -> TokenTree2 {
+        let spacing = if joint {
+            Spacing::Joint
+        } else {
+            Spacing::Alone
+        };
+        let mut punct2 = Punct2::new(ch, spacing);
+        punct2.set_span(span.into());
+        punct2.into()
+    }
+
+    fn literal(literal: Literal, span: Span) -> TokenTree2 {
+        let mut literal2 =
+            Literal2::try_from(literal).expect("bool literals are emitted as identifiers");
+        literal2.set_span(span.into());
+        literal2.into()
+    }
+}
+
+impl TryFrom<TokenStream2> for TokenStream {
+    type Error = ParseError;
+
+    /// Converts a [`proc_macro2::TokenStream`] into a [`TokenStream`].
+    ///
+    /// Runs of [`Spacing::Joint`] punctuation are glued back together into multi-character
+    /// [`Punct`]s (i.e. `<<=`), a `'` joined to an identifier becomes a [`Token::Lifetime`], and
+    /// invisible (`Delimiter::None`) groups are flattened into the surrounding stream. Tokens
+    /// sin cannot represent, such as C string literals, result in a [`ParseError`].
+    fn try_from(value: TokenStream2) -> Result<Self, Self::Error> {
+        TokenStream::try_from_foreign(value)
+    }
+}
+
+impl From<TokenStream> for TokenStream2 {
+    /// Converts a [`TokenStream`] into a [`proc_macro2::TokenStream`].
+    ///
+    /// Tokens that originated from proc macro input keep their original compiler spans, while
+    /// tokens using fallback [`Span`]s are emitted with call-site (or mixed-site) resolution.
+    fn from(value: TokenStream) -> Self {
+        value.into_foreign()
+    }
+}
+
+fn group2(delimiter: Delimiter, content: TokenStream2, span: Span) -> Group2 {
+    let delimiter = match delimiter {
+        Delimiter::Paren => Delimiter2::Parenthesis,
+        Delimiter::Brace => Delimiter2::Brace,
+        Delimiter::Bracket => Delimiter2::Bracket,
+    };
+    let mut group2 = Group2::new(delimiter, content);
+    group2.set_span(span.into());
+    group2
+}
+
+fn word_to_ident2(word: &str, span: Span2) -> Ident2 {
+    match word.strip_prefix("r#") {
+        Some(raw) => Ident2::new_raw(raw, span),
+        None => Ident2::new(word, span),
+    }
+}
+
+#[test]
+fn test_proc_macro2_round_trip() {
+    let source = "pub fn foo<'a>(x: &'a u8) -> bool { *x <<= 2; x != 'c' && true }";
+    let tokens2: TokenStream2 = source.parse().unwrap();
+    let tokens: TokenStream = tokens2.clone().try_into().unwrap();
+    let expected: TokenStream = source.parse().unwrap();
+    let strs = |tokens: &TokenStream| tokens.iter().map(|tt| tt.to_string()).collect::<Vec<_>>();
+    let squash = |tokens: TokenStream2| tokens.to_string().replace(' ', "");
+    assert_eq!(
+        strs(&tokens)[..8],
+        ["pub", "fn", "foo", "<", "'a", ">", "()", "->"]
+    );
+    assert_eq!(tokens.len(), expected.len());
+    let back: TokenStream2 = tokens.into();
+    assert_eq!(squash(back), squash(tokens2.clone()));
+    let lexed_back: TokenStream2 = expected.into();
+    assert_eq!(squash(lexed_back), squash(tokens2));
+}
+
+#[test]
+fn test_proc_macro2_spans_are_fallback() {
+    let tokens2: TokenStream2 = "a + b".parse().unwrap();
+    let tokens: TokenStream = tokens2.try_into().unwrap();
+    assert!(tokens.iter().all(|tt| tt.span().is_fallback()));
+    let span: Span2 = Span::mixed_site().into();
+    let span: Span = span.into();
+    assert!(span.is_fallback());
+}
+
+#[test]
+fn test_proc_macro2_pieces() {
+    let ident: Ident = Ident2::new("my_ident", Span2::call_site()).into();
+    assert_eq!(ident, "my_ident");
+    let ident2: Ident2 = Ident::new("r#type").into();
+    assert_eq!(ident2.to_string(), "r#type");
+    let literal: Literal = Literal2::u8_suffixed(7).try_into().unwrap();
+    assert_eq!(literal.to_string(), "7u8");
+    let literal2: Literal2 = Literal::String("hi \"there\"".into()).try_into().unwrap();
+    assert_eq!(literal2.to_string(), "\"hi \\\"there\\\"\"");
+    assert!(Literal2::try_from(Literal::Bool(true)).is_err());
+    let bool2: TokenStream2 = TokenTree::Leaf(t![true], Span::call_site()).into();
+    assert_eq!(bool2.to_string(), "true");
+    let group2 = Group2::new(Delimiter2::Bracket, "x, y".parse().unwrap());
+    let group: Group = group2.try_into().unwrap();
+    assert_eq!(group.delimiter, Delimiter::Bracket);
+    assert_eq!(group.content.len(), 3);
+    let group2: Group2 = group.into();
+    assert_eq!(group2.to_string(), "[x , y]");
+    let shl: TokenStream2 = TokenTree::Leaf(t![<<=], Span::call_site()).into();
+    assert_eq!(shl.to_string(), "<<=");
+    let tt: TokenTree = TokenTree2::from(Punct2::new('<', Spacing::Joint))
+        .try_into()
+        .unwrap();
+    assert_eq!(Token::from(tt), t![<]);
+    assert!(TokenTree::try_from(TokenTree2::from(Punct2::new('\'', Spacing::Joint))).is_err());
+}
+
+#[test]
+fn test_proc_macro2_conversion_errors() {
+    let tokens2: TokenStream2 = "let s = c\"hi\";".parse().unwrap();
+    let err = TokenStream::try_from(tokens2).unwrap_err();
+    assert!(err.messages[0]
+        .message
+        .starts_with("unsupported literal `c\"hi\"`"));
+    let group2: TokenStream2 = "(c\"hi\")".parse().unwrap();
+    let Some(TokenTree2::Group(group2)) = group2.into_iter().next() else {
+        panic!("expected a group");
+    };
+    let err = Group::try_from(group2).unwrap_err();
+    assert_eq!(err.messages.len(), 1);
+    assert!(err.messages[0]
+        .message
+        .starts_with("unsupported literal `c\"hi\"`"));
+}
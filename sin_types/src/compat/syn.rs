@@ -0,0 +1,306 @@
+use crate::*;
+use core::{
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens as ToTokens2;
+use std::rc::Rc;
+use syn::parse::{ParseStream, Parser as Parser2};
+
+/// Wraps any [`syn::parse::Parse`] type so it can be parsed from (and turned back into) sin
+/// tokens, making it possible to mix sin and `syn` parsing within the same [`Parser`].
+///
+/// When parsed, a [`Syn`] consumes exactly the tokens `syn` consumed when parsing `T` starting
+/// at the current position of the [`Parser`], leaving the rest of the input untouched.
+///
+/// Equality, ordering and hashing are based on the consumed tokens. The `syn` value is shared
+/// between clones, so `T` does not need to implement [`Clone`].
+pub struct Syn<T> {
+    inner: Rc<T>,
+    tokens: TokenStream,
+    span: Span,
+}
+
+impl<T: ToTokens2> Syn<T> {
+    /// Wraps an existing `syn` value, generating its sin tokens via [`quote::ToTokens`].
+    ///
+    /// Fails if the tokens of `inner` cannot be represented by sin, i.e. C string literals.
+    pub fn new(inner: T) -> ParseResult<Self> {
+        let tokens = TokenStream::try_from(inner.to_token_stream())?;
+        let span = tokens_span(&tokens).unwrap_or_default();
+        Ok(Syn {
+            inner: Rc::new(inner),
+            tokens,
+            span,
+        })
+    }
+}
+
+impl<T> Syn<T> {
+    /// Returns the underlying `syn` value, cloning it only if this [`Syn`] has been cloned.
+    pub fn into_inner(self) -> T
+    where
+        T: Clone,
+    {
+        Rc::unwrap_or_clone(self.inner)
+    }
+
+    /// Returns the sin tokens that were consumed when this [`Syn`] was parsed.
+    pub fn tokens(&self) -> &TokenStream {
+        &self.tokens
+    }
+}
+
+impl<T> Clone for Syn<T> {
+    fn clone(&self) -> Self {
+        Syn {
+            inner: self.inner.clone(),
+            tokens: self.tokens.clone(),
+            span: self.span,
+        }
+    }
+}
+
+impl<T> Deref for Syn<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T> PartialEq for Syn<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.tokens == other.tokens
+    }
+}
+
+impl<T> Eq for Syn<T> {}
+
+impl<T> PartialOrd for Syn<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Syn<T> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.tokens.cmp(&other.tokens)
+    }
+}
+
+impl<T> Hash for Syn<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tokens.hash(state);
+    }
+}
+
+impl<T: ToTokens2> Debug for Syn<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Syn")
+            .field(&self.inner.to_token_stream().to_string())
+            .finish()
+    }
+}
+
+impl<T> Spanned for Syn<T> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<T: ToTokens2> ToTokens for Syn<T> {
+    fn to_token_stream(&self) -> TokenStream {
+        self.tokens.clone()
+    }
+}
+
+/// The number of sin tokens initially converted for `syn` to parse a [`Syn`] from.
+const SYN_WINDOW: usize = 16;
+
+/// How many sin tokens past the end of what it consumed `syn` may have looked at, i.e. via
+/// `peek3`, which must therefore exist whole for a parse of a truncated window to be trusted.
+const SYN_LOOKAHEAD: usize = 3;
+
+impl<T: syn::parse::Parse + ToTokens2> Parse for Syn<T> {
+    fn parse<'a, S: Default + Clone>(input: &mut Parser<'a, S>) -> ParseResult<Self> {
+        // Only a window of the remaining input is converted for syn, which grows until syn
+        // stops short of its end, so that parsing many `Syn`s in a row stays linear. Each sin
+        // token may expand to several proc_macro2 token trees (i.e. `<<=`, `'a`), so we track
+        // how many trees each one produces to map syn's progress back onto sin tokens.
+        let mut tokens = input.clone();
+        let fallback_span = tokens.peek().map_or(input.span(), |tt| tt.span());
+        let parser = |stream: ParseStream| {
+            let inner = stream.parse::<T>()?;
+            let rest: TokenStream2 = stream.parse()?;
+            Ok((inner, rest.into_iter().count()))
+        };
+        let mut window: Vec<TokenTree> = Vec::new();
+        let mut widths: Vec<usize> = Vec::new();
+        let mut stream2 = TokenStream2::new();
+        let (inner, count, partial) = loop {
+            let target = (window.len() * 2).max(SYN_WINDOW);
+            for tt in tokens.by_ref().take(target - window.len()) {
+                let trees: TokenStream2 = tt.clone().into();
+                widths.push(trees.clone().into_iter().count());
+                stream2.extend(trees);
+                window.push(tt);
+            }
+            let truncated = tokens.peek().is_some();
+            match parser.parse2(stream2.clone()) {
+                Ok((inner, rest)) => {
+                    let (count, partial) = consumed_tokens(&widths, rest);
+                    let unconsumed = window.len() - count - usize::from(partial);
+                    if !truncated || unconsumed >= SYN_LOOKAHEAD {
+                        break (inner, count, partial);
+                    }
+                }
+                Err(err) if !truncated => return Err(syn_error(err, fallback_span)),
+                Err(_) => (),
+            }
+        };
+        if partial {
+            let tt = &window[count];
+            return Err(ParseError::new().error(
+                tt.span(),
+                format!("syn consumed only part of `{}`", Token::from(tt.clone())),
+            ));
+        }
+        let tokens = TokenStream::from_tokens(&window[..count]);
+        let span = tokens_span(&tokens).unwrap_or(fallback_span);
+        for _ in 0..count {
+            input.next();
+        }
+        Ok(Syn {
+            inner: Rc::new(inner),
+            tokens,
+            span,
+        })
+    }
+
+    /// Describes this type by the name of the wrapped syn type, i.e. `Expr`.
+    fn description() -> InStr {
+        type_description::<T>()
+    }
+}
+
+/// Maps the number of trees `syn` left unconsumed back onto the sin tokens it was given, each
+/// of which expanded to the corresponding number of `widths` trees. Returns how many of the
+/// sin tokens were consumed entirely, and whether the next one was consumed only partially.
+fn consumed_tokens(widths: &[usize], rest: usize) -> (usize, bool) {
+    let mut consumed = widths.iter().sum::<usize>() - rest;
+    let mut count = 0;
+    while consumed > 0 && widths[count] <= consumed {
+        consumed -= widths[count];
+        count += 1;
+    }
+    (count, consumed > 0)
+}
+
+/// Joins the [`Span`]s of the first and last of `tokens`, which is the [`Span`] of a [`Syn`].
+fn tokens_span(tokens: &TokenStream) -> Option<Span> {
+    let first = tokens.as_slice().first()?.span();
+    let last = tokens.as_slice().last()?.span();
+    Some(first.join(last).unwrap_or(first))
+}
+
+/// Converts a [`syn::Error`] into a [`ParseError`], using `fallback_span` for any messages
+/// whose span does not map back onto proc macro input.
+fn syn_error(err: syn::Error, fallback_span: Span) -> ParseError {
+    let mut error = ParseError::new();
+    for err in err {
+        let span: Span = err.span().into();
+        let span = match span.is_fallback() {
+            true => fallback_span,
+            false => span,
+        };
+        error = error.error(span, err.to_string());
+    }
+    error
+}
+
+#[test]
+fn test_parse_syn_type() {
+    let tokens: TokenStream = "Vec<Option<u8>>, rest".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    let ty: Syn<syn::Type> = input.parse().unwrap();
+    assert!(matches!(*ty, syn::Type::Path(_)));
+    assert_eq!(ty.tokens().len(), 6);
+    assert_eq!(ty.span().source_text(), Some("Vec<Option<u8>>"));
+    assert_eq!(Token::from(input.next().unwrap()), t![,]);
+    let rest: Ident = input.parse().unwrap();
+    assert_eq!(rest, "rest");
+}
+
+#[test]
+fn test_parse_syn_between_sin_tokens() {
+    let tokens: TokenStream = "x = a + b * 2; 'a: loop {}".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    let x: Ident = input.parse().unwrap();
+    assert_eq!(x, "x");
+    assert_eq!(Token::from(input.next().unwrap()), t![=]);
+    let expr: Syn<syn::Expr> = input.parse().unwrap();
+    assert!(matches!(*expr, syn::Expr::Binary(_)));
+    assert_eq!(expr.tokens().len(), 5);
+    assert_eq!(Token::from(input.next().unwrap()), t![;]);
+    let lifetime: Syn<syn::Lifetime> = input.parse().unwrap();
+    assert_eq!(lifetime.ident, "a");
+    assert_eq!(lifetime.tokens().len(), 1);
+    assert_eq!(input.count(), 3);
+}
+
+#[test]
+fn test_parse_syn_errors() {
+    let tokens: TokenStream = "<<= 3".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    let err = input.parse::<Syn<syn::Token![<]>>().unwrap_err();
+    assert_eq!(err.messages[0], "syn consumed only part of `<<=`");
+    let err = input.parse::<Syn<syn::Ident>>().unwrap_err();
+    assert_eq!(err.messages[0], "expected identifier");
+    assert_eq!(input.count(), 2);
+}
+
+#[test]
+fn test_parse_syn_windows() {
+    // longer than the initial window, so syn has to be given more of the input
+    let source = (0..40)
+        .map(|i| format!("a{i}"))
+        .collect::<Vec<_>>()
+        .join(" + ");
+    let tokens: TokenStream = format!("{source}; {source}").parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    let expr: Syn<syn::Expr> = input.parse().unwrap();
+    assert_eq!(expr.tokens().len(), 79);
+    assert_eq!(Token::from(input.next().unwrap()), t![;]);
+    let tokens: TokenStream = "a b c d e f g h i j k l m n o p q r s t u v w x y z"
+        .parse()
+        .unwrap();
+    let idents = parse::<Vec<Syn<syn::Ident>>>(&tokens).unwrap();
+    assert_eq!(idents.len(), 26);
+    // errors past the initial window are still found
+    let tokens: TokenStream = format!("{source} +").parse().unwrap();
+    let err = parse::<Syn<syn::Expr>>(&tokens).unwrap_err();
+    assert_eq!(err.messages.len(), 1);
+    // lookahead is counted in sin tokens, however many trees each of them expands to
+    assert_eq!(consumed_tokens(&[1, 1, 3, 3], 6), (2, false));
+    assert_eq!(consumed_tokens(&[1, 3, 1], 3), (1, true));
+    let tokens: TokenStream = "a b c d e f g h i j k l m n <<= <<= o".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    let idents = input.parse::<Vec<Syn<syn::Ident>>>().unwrap();
+    assert_eq!(idents.len(), 14);
+    assert_eq!(input.count(), 3);
+}
+
+#[test]
+fn test_syn_to_tokens() {
+    let ty: syn::Type = syn::parse_quote!(&'static str);
+    let wrapped = Syn::new(ty).unwrap();
+    assert_eq!(wrapped.to_token_stream().len(), 3);
+    let parsed = parse::<Syn<syn::Type>>(wrapped.to_token_stream()).unwrap();
+    assert_eq!(parsed, wrapped);
+    assert_eq!(parsed.span(), wrapped.span());
+    assert_eq!(format!("{parsed:?}"), "Syn(\"& 'static str\")");
+    assert!(matches!(parsed.into_inner(), syn::Type::Reference(_)));
+}
@@ -0,0 +1,345 @@
+//! Renders [`ParseError`]s the way rustc renders its diagnostics, with source snippets for
+//! errors pointing at fallback [`Span`]s, i.e. when parsing strings in tests and tools.
+
+use crate::{
+    parsing::{suggestion_message, ErrorMessage, Label, Suggestion},
+    span::SourceExcerpt,
+    *,
+};
+use core::fmt::Write;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+
+/// Renders [`ParseError`]s as text, like rustc:
+///
+/// ```text
+/// error: expected `->`, found `=>`
+///  --> 1:8
+///   |
+/// 1 | fn a() => b { a }
+///   |        ^^
+///   | -- in this function
+///   |
+///   = note: return types are preceded by `->`
+/// ```
+///
+/// Only [`Span`]s using the fallback implementation know where they are within their source
+/// text, so errors pointing at proc macro input are rendered without a snippet. This is
+/// also how [`ParseError`] and [`ErrorMessage`] implement [`Display`](core::fmt::Display), and
+/// how [`ParseError`] implements [`Debug`](core::fmt::Debug).
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Renderer {
+    color: bool,
+}
+
+/// Where a [`SourceExcerpt`] starts, within the line it starts on.
+struct Location {
+    /// The 1-based line number.
+    line: usize,
+    /// The 0-based column, in characters.
+    column: usize,
+    /// The text of the whole line, without its line break.
+    text: &'static str,
+    /// The byte range of the excerpt within `text`, cut off at the end of the line.
+    range: core::ops::Range<usize>,
+}
+
+impl Location {
+    fn new(excerpt: SourceExcerpt) -> Self {
+        let source = excerpt.source();
+        let start = excerpt.start().min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let end = excerpt.end().clamp(start, line_end);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        Location {
+            line: source[..start].matches('\n').count() + 1,
+            column: source[line_start..start].chars().count(),
+            text,
+            range: (start - line_start)..(end - line_start).min(text.len()),
+        }
+    }
+
+    /// The width of the excerpt in characters, for underlining it.
+    fn width(&self) -> usize {
+        self.text[self.range.clone()].chars().count().max(1)
+    }
+}
+
+/// An underlined [`Location`] within a snippet.
+struct Annotation<'m> {
+    location: Location,
+    primary: bool,
+    label: Option<&'m Label>,
+}
+
+impl Renderer {
+    pub fn new() -> Self {
+        Renderer::default()
+    }
+
+    /// Colors the output with ANSI escape codes, for printing to a terminal.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Renders every message of `error`, separated by blank lines.
+    pub fn render(&self, error: &ParseError) -> String {
+        let messages: Vec<String> = error
+            .messages
+            .iter()
+            .map(|message| self.render_message(message))
+            .collect();
+        messages.join("\n\n")
+    }
+
+    pub fn render_message(&self, message: &ErrorMessage) -> String {
+        let mut out = String::new();
+        let header = format!(": {}", message.message);
+        out.push_str(&self.paint(RED, "error"));
+        out.push_str(&self.paint(BOLD, &header));
+
+        // labels are only shown in the snippet if they are in the same source as the error
+        let primary = message.span.source_excerpt();
+        let source = primary
+            .into_iter()
+            .chain(
+                message
+                    .labels
+                    .iter()
+                    .filter_map(|l| l.span.source_excerpt()),
+            )
+            .map(|excerpt| excerpt.source())
+            .next();
+        let mut annotations = Vec::new();
+        let mut loose_labels = Vec::new();
+        if let Some(excerpt) = primary {
+            annotations.push(Annotation {
+                location: Location::new(excerpt),
+                primary: true,
+                label: None,
+            });
+        }
+        for label in &message.labels {
+            match label.span.source_excerpt() {
+                Some(excerpt) if Some(excerpt.source()) == source => annotations.push(Annotation {
+                    location: Location::new(excerpt),
+                    primary: false,
+                    label: Some(label),
+                }),
+                _ => loose_labels.push(label),
+            }
+        }
+        annotations.sort_by_key(|annotation| annotation.location.line);
+        let mut suggestions = Vec::new();
+        let mut loose_suggestions = Vec::new();
+        for suggestion in &message.suggestions {
+            match suggestion.span.source_excerpt() {
+                Some(excerpt) => suggestions.push((Location::new(excerpt), suggestion)),
+                None => loose_suggestions.push(suggestion),
+            }
+        }
+
+        let width = annotations
+            .iter()
+            .map(|annotation| &annotation.location)
+            .chain(suggestions.iter().map(|(location, _)| location))
+            .map(|location| location.line.to_string().len())
+            .max()
+            .unwrap_or(0);
+        if let Some(Annotation { location, .. }) = annotations.iter().find(|a| a.primary) {
+            let arrow = format!("{:width$}--> ", "");
+            let _ = write!(
+                out,
+                "\n{}{}:{}",
+                self.paint(BLUE, &arrow),
+                location.line,
+                location.column + 1
+            );
+        }
+        if !annotations.is_empty() {
+            out.push_str(&self.gutter(width, None));
+            let mut previous_line = 0;
+            for annotation in &annotations {
+                let location = &annotation.location;
+                if location.line != previous_line {
+                    out.push_str(&self.gutter(width, Some(location.line)));
+                    out.push(' ');
+                    out.push_str(location.text);
+                    previous_line = location.line;
+                }
+                let (style, mark) = match annotation.primary {
+                    true => (RED, "^"),
+                    false => (BLUE, "-"),
+                };
+                let mut underline = mark.repeat(location.width());
+                if let Some(label) = annotation.label {
+                    underline.push(' ');
+                    underline.push_str(&label.message);
+                }
+                out.push_str(&self.gutter(width, None));
+                out.push_str(&" ".repeat(location.column + 1));
+                out.push_str(&self.paint(style, &underline));
+            }
+            let footer = !loose_labels.is_empty()
+                || !message.notes.is_empty()
+                || !message.help.is_empty()
+                || !message.suggestions.is_empty();
+            if footer {
+                out.push_str(&self.gutter(width, None));
+            }
+        }
+
+        for label in loose_labels {
+            let _ = match label.span.source_text() {
+                Some(text) => write!(out, "\n{:width$} - `{text}`: {}", "", label.message),
+                None => write!(out, "\n{:width$} - {}", "", label.message),
+            };
+        }
+        for note in &message.notes {
+            let _ = write!(
+                out,
+                "\n{:width$} {} {note}",
+                "",
+                self.paint(BOLD, "= note:")
+            );
+        }
+        for help in &message.help {
+            let _ = write!(
+                out,
+                "\n{:width$} {} {help}",
+                "",
+                self.paint(BOLD, "= help:")
+            );
+        }
+        for suggestion in loose_suggestions {
+            let help = self.paint(BOLD, "= help:");
+            let _ = write!(
+                out,
+                "\n{:width$} {help} {}",
+                "",
+                suggestion_message(suggestion)
+            );
+        }
+        for (location, suggestion) in &suggestions {
+            out.push_str(&self.render_suggestion(width, location, suggestion));
+        }
+        out
+    }
+
+    /// Renders `suggestion` as a snippet of the line with the replacement applied.
+    fn render_suggestion(
+        &self,
+        width: usize,
+        location: &Location,
+        suggestion: &Suggestion,
+    ) -> String {
+        let mut out = format!("\n{}: {}", self.paint(BOLD, "help"), suggestion.message);
+        out.push_str(&self.gutter(width, None));
+        out.push_str(&self.gutter(width, Some(location.line)));
+        out.push(' ');
+        out.push_str(&location.text[..location.range.start]);
+        out.push_str(&suggestion.replacement);
+        out.push_str(&location.text[location.range.end..]);
+        out.push_str(&self.gutter(width, None));
+        out.push_str(&" ".repeat(location.column + 1));
+        let underline = "~".repeat(suggestion.replacement.chars().count().max(1));
+        out.push_str(&self.paint(BLUE, &underline));
+        out
+    }
+
+    /// Starts a new snippet line with the gutter, showing the line number if there is one.
+    fn gutter(&self, width: usize, line: Option<usize>) -> String {
+        let gutter = match line {
+            Some(line) => format!("{line:>width$} |"),
+            None => format!("{:width$} |", ""),
+        };
+        format!("\n{}", self.paint(BLUE, &gutter))
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        match self.color {
+            true => format!("{style}{text}{RESET}"),
+            false => text.to_string(),
+        }
+    }
+}
+
+#[test]
+fn test_render_snippets() {
+    let source = "struct Foo {\n    a: u8,\n    a => u16,\n}";
+    let tokens: TokenStream = source.parse().unwrap();
+    let Some(TokenTree::Tree(group)) = tokens.iter().nth(2) else {
+        panic!("expected the fields");
+    };
+    let fields: Vec<Span> = group.content.iter().map(|token| token.span()).collect();
+    let error = ParseError::new()
+        .error(fields[5], "expected `:`, found `=>`")
+        .label(fields[0], "first defined here")
+        .note("fields are declared as `name: Type`")
+        .suggestion(fields[5], "use a colon", ":");
+    assert_eq!(
+        error.to_string(),
+        "error: expected `:`, found `=>`
+ --> 3:7
+  |
+2 |     a: u8,
+  |     - first defined here
+3 |     a => u16,
+  |       ^^
+  |
+  = note: fields are declared as `name: Type`
+help: use a colon
+  |
+3 |     a : u16,
+  |       ~"
+    );
+    let error = error.error(fields[6], "unexpected `u16`");
+    // messages are separated by a blank line, also when debugging
+    assert_eq!(format!("{error:?}"), error.to_string());
+    assert!(error.to_string().contains("~\n\nerror: unexpected `u16`"));
+    assert!(error.to_string().ends_with(
+        "error: unexpected `u16`
+ --> 3:10
+  |
+3 |     a => u16,
+  |          ^^^"
+    ));
+}
+
+#[test]
+fn test_render_without_source() {
+    let error = ParseError::new()
+        .error(Span::call_site(), "unexpected end of input")
+        .label(Span::call_site(), "macro invoked here")
+        .help("add an item")
+        .suggestion(Span::call_site(), "for example", "struct Foo;");
+    assert_eq!(
+        error.to_string(),
+        "error: unexpected end of input
+ - macro invoked here
+ = help: add an item
+ = help: for example: `struct Foo;`"
+    );
+    let colored = Renderer::new().color(true).render(&error);
+    assert!(colored.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: unexpected end of input\x1b[0m"));
+    // long sources widen the gutter
+    let source = format!("{}a b", "\n".repeat(9));
+    let tokens: TokenStream = source.as_str().parse().unwrap();
+    let spans: Vec<Span> = tokens.iter().map(|token| token.span()).collect();
+    let error = ParseError::new().error(spans[1], "unexpected `b`");
+    assert_eq!(
+        error.to_string(),
+        "error: unexpected `b`
+  --> 10:3
+   |
+10 | a b
+   |   ^"
+    );
+}
@@ -0,0 +1,549 @@
+use crate::*;
+
+/// Converts plain Rust source text into a [`TokenStream`].
+///
+/// Every token produced by the [`Lexer`] is given a fallback [`Span`] (see
+/// [`Span::new_within`]) pointing at its exact byte range within the input, so parse errors
+/// and [`Span::source_text`] refer back to the original source. Groups are nested according to
+/// their delimiters and comments are discarded, except for doc comments which are converted to
+/// `#[doc = "..."]` attributes just like `rustc` does.
+///
+/// Identifiers that are not Rust [`Keyword`]s are lexed as [`Token::Ident`], unless they have
+/// been registered via [`Lexer::custom_keyword`], in which case they are lexed as
+/// [`Token::CustomKeyword`].
+///
+/// [`TokenStream`] also implements [`FromStr`](core::str::FromStr) using a default [`Lexer`].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Lexer {
+    custom_keywords: Vec<InStr>,
+}
+
+impl Lexer {
+    pub fn new() -> Self {
+        Lexer::default()
+    }
+
+    /// Registers `keyword` as a custom keyword, causing occurrences of it in the input to be
+    /// lexed as [`Token::CustomKeyword`] instead of [`Token::Ident`].
+    pub fn custom_keyword(mut self, keyword: impl Into<InStr>) -> Self {
+        self.custom_keywords.push(keyword.into());
+        self
+    }
+
+    /// Lexes the specified `source` into a [`TokenStream`].
+    ///
+    /// Returns a [`ParseError`] spanning the offending input if the source contains unbalanced
+    /// delimiters, malformed literals, or characters that cannot start a token.
+    pub fn lex(&self, source: impl Into<InStr>) -> ParseResult<TokenStream> {
+        let source: InStr = source.into();
+        Cursor {
+            lexer: self,
+            source,
+            text: source.as_str(),
+            pos: 0,
+        }
+        .lex()
+    }
+}
+
+struct Frame {
+    delimiter: Delimiter,
+    open: usize,
+    tokens: TokenStream,
+}
+
+struct Cursor<'l> {
+    lexer: &'l Lexer,
+    source: InStr,
+    text: &'static str,
+    pos: usize,
+}
+
+fn is_ident_start(c: char) -> bool {
+    c == '_' || c.is_alphabetic()
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c == '_' || c.is_alphanumeric()
+}
+
+impl<'l> Cursor<'l> {
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span::new_within(self.source, start..end)
+    }
+
+    fn error(&self, start: usize, end: usize, message: impl Into<InStr>) -> ParseError {
+        ParseError::new().error(self.span(start, end), message)
+    }
+
+    fn rest(&self) -> &'static str {
+        &self.text[self.pos..]
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth_char(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat_while(&mut self, predicate: impl Fn(char) -> bool) {
+        while let Some(c) = self.peek_char() {
+            if !predicate(c) {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    fn lex(mut self) -> ParseResult<TokenStream> {
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut current = TokenStream::new_spanned(Span::new(self.source));
+        loop {
+            self.skip_trivia(&mut current)?;
+            let start = self.pos;
+            let Some(c) = self.peek_char() else {
+                break;
+            };
+            match c {
+                '(' | '[' | '{' => {
+                    self.bump();
+                    let delimiter = match c {
+                        '(' => Delimiter::Paren,
+                        '[' => Delimiter::Bracket,
+                        _ => Delimiter::Brace,
+                    };
+                    stack.push(Frame {
+                        delimiter,
+                        open: start,
+                        tokens: core::mem::take(&mut current),
+                    });
+                }
+                ')' | ']' | '}' => {
+                    self.bump();
+                    let Some(frame) = stack.pop() else {
+                        return Err(self.error(
+                            start,
+                            self.pos,
+                            format!("unexpected closing delimiter `{c}`"),
+                        ));
+                    };
+                    if frame.delimiter.close() != c {
+                        return Err(self.error(
+                            start,
+                            self.pos,
+                            format!(
+                                "mismatched closing delimiter `{c}`, expected `{}`",
+                                frame.delimiter.close()
+                            ),
+                        ));
+                    }
+                    let mut content = core::mem::replace(&mut current, frame.tokens);
+                    content.set_span(self.span(frame.open + 1, start));
+                    current.push(TokenTree::Tree(Group {
+                        delimiter: frame.delimiter,
+                        span: self.span(frame.open, self.pos),
+                        span_open: self.span(frame.open, frame.open + 1),
+                        span_close: self.span(start, self.pos),
+                        content,
+                    }));
+                }
+                _ => {
+                    let token = self.lex_token()?;
+                    current.push(TokenTree::Leaf(token, self.span(start, self.pos)));
+                }
+            }
+        }
+        if let Some(frame) = stack.pop() {
+            return Err(self.error(
+                frame.open,
+                frame.open + 1,
+                format!("unclosed delimiter `{}`", frame.delimiter.open()),
+            ));
+        }
+        Ok(current)
+    }
+
+    /// Skips whitespace and comments, pushing the attribute equivalent of any doc comments onto
+    /// `tokens`.
+    fn skip_trivia(&mut self, tokens: &mut TokenStream) -> ParseResult<()> {
+        loop {
+            let start = self.pos;
+            let rest = self.rest();
+            if rest.starts_with(|c: char| c.is_whitespace()) {
+                self.eat_while(char::is_whitespace);
+            } else if rest.starts_with("//") {
+                let end = rest
+                    .find('\n')
+                    .map(|i| self.pos + i)
+                    .unwrap_or(self.text.len());
+                let comment = &self.text[start..end];
+                self.pos = end;
+                if comment.starts_with("///") && !comment.starts_with("////") {
+                    self.push_doc(tokens, &comment[3..], false, start);
+                } else if let Some(doc) = comment.strip_prefix("//!") {
+                    self.push_doc(tokens, doc, true, start);
+                }
+            } else if rest.starts_with("/*") {
+                let mut depth = 0;
+                while !self.rest().is_empty() {
+                    if self.rest().starts_with("/*") {
+                        depth += 1;
+                        self.pos += 2;
+                    } else if self.rest().starts_with("*/") {
+                        depth -= 1;
+                        self.pos += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        self.bump();
+                    }
+                }
+                if depth != 0 {
+                    return Err(self.error(start, start + 2, "unterminated block comment"));
+                }
+                let comment = &self.text[start..self.pos];
+                let body = &comment[3.min(comment.len() - 2)..comment.len() - 2];
+                if comment.starts_with("/**") && !comment.starts_with("/***") && comment != "/**/" {
+                    self.push_doc(tokens, body, false, start);
+                } else if comment.starts_with("/*!") {
+                    self.push_doc(tokens, body, true, start);
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn push_doc(&self, tokens: &mut TokenStream, doc: &str, inner: bool, start: usize) {
+        let span = self.span(start, self.pos);
+        tokens.push(TokenTree::Leaf(t![#], span));
+        if inner {
+            tokens.push(TokenTree::Leaf(t![!], span));
+        }
+        let mut content = TokenStream::new_spanned(span);
+        content.push(TokenTree::Leaf(t![#doc], span));
+        content.push(TokenTree::Leaf(t![=], span));
+        content.push(TokenTree::Leaf(
+            Token::Literal(Literal::String(InStr::from(doc))),
+            span,
+        ));
+        tokens.push(TokenTree::Tree(Group {
+            delimiter: Delimiter::Bracket,
+            span,
+            span_open: span,
+            span_close: span,
+            content,
+        }));
+    }
+
+    fn lex_token(&mut self) -> ParseResult<Token> {
+        let start = self.pos;
+        let c = self.peek_char().unwrap();
+        let next = self.peek_nth_char(1);
+        match (c, next) {
+            ('r', Some('#')) if self.peek_nth_char(2).is_some_and(is_ident_start) => {
+                self.pos += 2;
+                self.eat_while(is_ident_continue);
+                Ok(Token::Ident(InStr::from(&self.text[start..self.pos])))
+            }
+            ('r', Some('"' | '#')) => self.lex_raw_string(1),
+            ('b', Some('r')) if matches!(self.peek_nth_char(2), Some('"' | '#')) => {
+                self.lex_raw_string(2)
+            }
+            ('b', Some('"')) => {
+                self.bump();
+                self.lex_quoted(start, '"')
+            }
+            ('b', Some('\'')) => {
+                self.bump();
+                self.lex_quoted(start, '\'')
+            }
+            ('"', _) => self.lex_quoted(start, '"'),
+            ('\'', _) => self.lex_quote_or_lifetime(),
+            (c, _) if c.is_ascii_digit() => self.lex_number(),
+            (c, _) if is_ident_start(c) => {
+                self.eat_while(is_ident_continue);
+                Ok(self.classify_word(&self.text[start..self.pos]))
+            }
+            _ => self.lex_punct(),
+        }
+    }
+
+    fn classify_word(&self, word: &'static str) -> Token {
+        match Token::from_word(word) {
+            Token::Ident(word) if self.lexer.custom_keywords.contains(&word) => {
+                Token::CustomKeyword(word)
+            }
+            token => token,
+        }
+    }
+
+    fn lex_punct(&mut self) -> ParseResult<Token> {
+        let start = self.pos;
+        for len in (1..=3).rev() {
+            let Some(candidate) = self.rest().get(..len) else {
+                continue;
+            };
+            if let Ok(punct) = Punct::try_from(candidate) {
+                self.pos += len;
+                return Ok(Token::Punct(punct));
+            }
+        }
+        let c = self.bump().unwrap();
+        Err(self.error(start, self.pos, format!("unknown start of token `{c}`")))
+    }
+
+    /// Lexes a (byte) string or (byte) char literal beginning at `start` whose opening `quote`
+    /// is the next char.
+    fn lex_quoted(&mut self, start: usize, quote: char) -> ParseResult<Token> {
+        self.bump();
+        loop {
+            match self.bump() {
+                Some('\\') => {
+                    self.bump();
+                }
+                Some(c) if c == quote => break,
+                Some(_) => {}
+                None => {
+                    let kind = if quote == '"' { "string" } else { "character" };
+                    return Err(self.error(
+                        start,
+                        self.text.len(),
+                        format!("unterminated {kind} literal"),
+                    ));
+                }
+            }
+        }
+        self.eat_while(is_ident_continue);
+        self.literal(start)
+    }
+
+    /// Lexes a raw (byte) string literal, where `prefix_len` is the length of the `r` or `br`
+    /// prefix.
+    fn lex_raw_string(&mut self, prefix_len: usize) -> ParseResult<Token> {
+        let start = self.pos;
+        self.pos += prefix_len;
+        let hashes = self.rest().len() - self.rest().trim_start_matches('#').len();
+        self.pos += hashes;
+        if self.peek_char() != Some('"') {
+            return Err(self.error(start, self.pos, "expected `\"` in raw string literal"));
+        }
+        self.bump();
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        let Some(end) = self.rest().find(&terminator) else {
+            return Err(self.error(start, self.text.len(), "unterminated raw string literal"));
+        };
+        self.pos += end + terminator.len();
+        self.eat_while(is_ident_continue);
+        self.literal(start)
+    }
+
+    fn lex_quote_or_lifetime(&mut self) -> ParseResult<Token> {
+        let start = self.pos;
+        let is_lifetime = match (self.peek_nth_char(1), self.peek_nth_char(2)) {
+            (_, Some('\'')) => false,
+            (Some(c), _) => is_ident_start(c),
+            _ => false,
+        };
+        if !is_lifetime {
+            return self.lex_quoted(start, '\'');
+        }
+        self.bump();
+        self.eat_while(is_ident_continue);
+        Ok(Token::Lifetime(InStr::from(&self.text[start..self.pos])))
+    }
+
+    fn lex_number(&mut self) -> ParseResult<Token> {
+        let start = self.pos;
+        let radix_prefix = matches!(
+            (self.peek_char(), self.peek_nth_char(1)),
+            (Some('0'), Some('x' | 'o' | 'b'))
+        );
+        if radix_prefix {
+            self.pos += 2;
+            self.eat_while(is_ident_continue);
+            return self.literal(start);
+        }
+        self.eat_while(|c| c.is_ascii_digit() || c == '_');
+        if self.peek_char() == Some('.')
+            && !matches!(self.peek_nth_char(1), Some(c) if c == '.' || is_ident_start(c))
+        {
+            self.bump();
+            self.eat_while(|c| c.is_ascii_digit() || c == '_');
+        }
+        if matches!(self.peek_char(), Some('e' | 'E')) {
+            let exponent = match self.peek_nth_char(1) {
+                Some('+' | '-') => self.peek_nth_char(2),
+                other => other,
+            };
+            if exponent.is_some_and(|c| c.is_ascii_digit() || c == '_') {
+                self.bump();
+                if matches!(self.peek_char(), Some('+' | '-')) {
+                    self.bump();
+                }
+                self.eat_while(|c| c.is_ascii_digit() || c == '_');
+            }
+        }
+        self.eat_while(is_ident_continue);
+        self.literal(start)
+    }
+
+    fn literal(&self, start: usize) -> ParseResult<Token> {
+        let text = &self.text[start..self.pos];
+        match Literal::parse(text) {
+            Ok(literal) => Ok(Token::Literal(literal)),
+            Err(err) => {
+                Err(self.error(start, self.pos, format!("invalid literal `{text}`: {err}")))
+            }
+        }
+    }
+}
+
+#[test]
+fn test_lex_basic() {
+    let tokens: TokenStream = "pub fn foo(x: u8) -> bool { x == 2 }".parse().unwrap();
+    let mut parser = tokens.to_parser();
+    assert_eq!(parser.next().unwrap().as_str(), "pub");
+    assert!(matches!(parser.next(), Some(TokenTree::Leaf(t![fn], _))));
+    let ident: Ident = parser.parse().unwrap();
+    assert_eq!(ident, "foo");
+    let Some(TokenTree::Tree(group)) = parser.next() else {
+        panic!("expected group");
+    };
+    assert_eq!(group.delimiter, Delimiter::Paren);
+    assert_eq!(group.span.source_text(), Some("(x: u8)"));
+    assert_eq!(group.span_open.source_text(), Some("("));
+    assert_eq!(group.content.len(), 3);
+    assert!(matches!(parser.next(), Some(TokenTree::Leaf(t![->], _))));
+    assert_eq!(parser.next().unwrap().as_str(), "bool");
+    let Some(TokenTree::Tree(body)) = parser.next() else {
+        panic!("expected group");
+    };
+    assert_eq!(body.delimiter, Delimiter::Brace);
+    assert_eq!(body.content.span().source_text(), Some(" x == 2 "));
+    assert!(parser.next().is_none());
+}
+
+#[test]
+fn test_lex_spans() {
+    let source = "let  x =\n    0x1F_u8 ;";
+    let tokens: TokenStream = source.parse().unwrap();
+    let spans: Vec<&str> = tokens
+        .iter()
+        .map(|tt| tt.span().source_text().unwrap())
+        .collect();
+    assert_eq!(spans, ["let", "x", "=", "0x1F_u8", ";"]);
+    assert_eq!(tokens.span().source_text(), Some(source));
+}
+
+#[test]
+fn test_lex_literals() {
+    let tokens: TokenStream =
+        r####"1 2.5 1e10 2.0f32 1. "a\"b" r#"raw"# b'x' b"bytes" br"raw" 'c' '\n' true"####
+            .parse()
+            .unwrap();
+    let literals: Vec<Token> = tokens.iter().map(|tt| tt.into()).collect();
+    assert!(literals.iter().all(|token| token.matches(pat![!lit])));
+    assert!(literals[0].matches(pat![!int]));
+    assert!(literals[1].matches(pat![!float]));
+    assert!(literals[2].matches(pat![!float]));
+    assert!(literals[3].matches(pat![!float]));
+    assert!(literals[4].matches(pat![!float]));
+    assert!(literals[5].matches(pat!["a\"b"]));
+    assert!(literals[6].matches(pat!["raw"]));
+    assert!(literals[7].matches(pat![!byte]));
+    assert!(literals[8].matches(pat![!bytestr]));
+    assert!(literals[9].matches(pat![!bytestr]));
+    assert!(literals[10].matches(pat!['c']));
+    assert!(literals[11].matches(pat!['\n']));
+    assert!(literals[12].matches(pat![!bool]));
+}
+
+#[test]
+fn test_lex_numbers_and_ranges() {
+    let tokens: TokenStream = "0..10 x.0 1..=2".parse().unwrap();
+    let strs: Vec<&str> = tokens
+        .iter()
+        .map(|tt| tt.span().source_text().unwrap())
+        .collect();
+    assert_eq!(strs, ["0", "..", "10", "x", ".", "0", "1", "..=", "2"]);
+}
+
+#[test]
+fn test_lex_puncts_and_lifetimes() {
+    let tokens: TokenStream = "a <<= b && 'a: loop {} impl<'de> _ r#struct"
+        .parse()
+        .unwrap();
+    let tokens: Vec<Token> = tokens.iter().map(|tt| tt.into()).collect();
+    assert_eq!(
+        tokens,
+        [
+            t![#a],
+            t![<<=],
+            t![#b],
+            t![&&],
+            t!['a],
+            t![:],
+            t![loop],
+            t![{}],
+            t![impl],
+            t![<],
+            t!['de],
+            t![>],
+            t![_],
+            Token::Ident("r#struct".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_lex_comments() {
+    let tokens: TokenStream = "a // comment\n/* block /* nested */ */ b /// doc\nc"
+        .parse()
+        .unwrap();
+    let strs: Vec<&str> = tokens.iter().map(|tt| tt.as_str()).collect();
+    assert_eq!(strs, ["a", "b", "#", "/// doc", "c"]);
+    let Some(TokenTree::Tree(doc)) = tokens.iter().nth(3) else {
+        panic!("expected doc attribute group");
+    };
+    let doc: Vec<Token> = doc.content.iter().map(|tt| tt.into()).collect();
+    assert_eq!(doc, [t![#doc], t![=], t![" doc"]]);
+}
+
+#[test]
+fn test_lex_custom_keywords() {
+    let tokens = Lexer::new().custom_keyword("state").lex("state x").unwrap();
+    let tokens: Vec<Token> = tokens.iter().map(|tt| tt.into()).collect();
+    assert_eq!(tokens, [t![state], t![#x]]);
+}
+
+#[test]
+fn test_lex_errors() {
+    let err = "foo(bar".parse::<TokenStream>().unwrap_err();
+    assert_eq!(err.messages[0], "unclosed delimiter `(`");
+    assert_eq!(err.messages[0].span.source_text(), Some("("));
+    let err = "foo(bar]".parse::<TokenStream>().unwrap_err();
+    assert_eq!(
+        err.messages[0],
+        "mismatched closing delimiter `]`, expected `)`"
+    );
+    assert_eq!(err.messages[0].span.source_text(), Some("]"));
+    let err = "a }".parse::<TokenStream>().unwrap_err();
+    assert_eq!(err.messages[0], "unexpected closing delimiter `}`");
+    let err = "x \"abc".parse::<TokenStream>().unwrap_err();
+    assert_eq!(err.messages[0], "unterminated string literal");
+    assert_eq!(err.messages[0].span.source_text(), Some("\"abc"));
+    let err = "0b102".parse::<TokenStream>().unwrap_err();
+    assert_eq!(err.messages[0].span.source_text(), Some("0b102"));
+    let err = "a \\ b".parse::<TokenStream>().unwrap_err();
+    assert_eq!(err.messages[0], "unknown start of token `\\`");
+}
@@ -1,3 +1,5 @@
+extern crate proc_macro;
+
 mod literal;
 pub use literal::*;
 mod token;
@@ -7,7 +9,11 @@ pub use interned::{InPath, InStr};
 pub use span::{Span, Spanned};
 pub mod token_stream;
 pub use token_stream::*;
+pub mod lexer;
+pub use lexer::*;
+pub mod diagnostic;
 pub mod parsing;
+pub use diagnostic::*;
 pub mod source;
 pub use parsing::*;
 pub mod traits;
@@ -17,6 +23,9 @@ pub use pattern::Pattern::*;
 pub use pattern::*;
 pub mod parsed;
 pub use parsed::*;
+pub mod compat;
+#[allow(unused_imports)]
+pub use compat::*;
 
 pub mod util {
     pub const fn assert_sync<T>()
@@ -45,6 +54,4 @@ pub mod util {
 }
 
 #[doc(hidden)]
-pub mod __private {
-    pub use litrs::Literal;
-}
+pub mod __private;
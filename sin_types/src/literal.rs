@@ -33,7 +33,7 @@ impl Display for IntLit {
 
 impl PartialOrd for IntLit {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        self.raw.partial_cmp(&other.raw)
+        Some(self.cmp(other))
     }
 }
 
@@ -79,7 +79,7 @@ impl Display for FloatLit {
 
 impl PartialOrd for FloatLit {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        self.raw.partial_cmp(&other.raw)
+        Some(self.cmp(other))
     }
 }
 
@@ -125,7 +125,7 @@ impl Display for ByteLit {
 
 impl PartialOrd for ByteLit {
     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        self.raw.partial_cmp(&other.raw)
+        Some(self.cmp(other))
     }
 }
 
@@ -264,6 +264,60 @@ impl Display for Literal {
     }
 }
 
+impl ToTokens for Literal {
+    /// Emits this [`Literal`] as a single [`TokenTree`] with a [`Span::call_site()`] span.
+    fn to_token_stream(&self) -> TokenStream {
+        TokenStream::from_tokens(&[TokenTree::Leaf(Token::Literal(*self), Span::call_site())])
+    }
+}
+
+macro_rules! impl_to_tokens_suffixed {
+    ($($ty:ident),*) => {
+        $(
+            impl ToTokens for $ty {
+                /// Emits this value as a suffixed [`Literal`] (i.e. `7u8`), preceded by a `-`
+                /// [`Punct`] if it is negative.
+                fn to_token_stream(&self) -> TokenStream {
+                    let text = format!("{}{}", self, stringify!($ty));
+                    let mut tokens = TokenStream::new();
+                    let text = match text.strip_prefix('-') {
+                        Some(text) => {
+                            tokens.push(TokenTree::Leaf(t![-], Span::call_site()));
+                            text
+                        }
+                        None => text.as_str(),
+                    };
+                    let literal =
+                        Literal::parse(text).expect("suffixed numbers are valid literals");
+                    tokens.extend(literal.to_token_stream());
+                    tokens
+                }
+            }
+        )*
+    };
+}
+
+impl_to_tokens_suffixed!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl ToTokens for bool {
+    fn to_token_stream(&self) -> TokenStream {
+        Literal::Bool(*self).to_token_stream()
+    }
+}
+
+impl ToTokens for char {
+    fn to_token_stream(&self) -> TokenStream {
+        Literal::Char(*self).to_token_stream()
+    }
+}
+
+impl ToTokens for String {
+    /// Emits this [`String`] as a string [`Literal`].
+    fn to_token_stream(&self) -> TokenStream {
+        Literal::String(self.as_str().into()).to_token_stream()
+    }
+}
+
 impl ParseLiteral for Literal {
     fn parse<S: AsRef<str>>(input: S) -> Result<Self, ParseError> {
         let sym = InStr::from(input.as_ref());
@@ -287,6 +341,18 @@ impl ParseLiteral for Literal {
     }
 }
 
+#[test]
+fn test_primitive_to_tokens() {
+    let tokens = 7u8.to_token_stream();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens.iter().next().unwrap().as_str(), "7u8");
+    let tokens = (-12i64).to_token_stream();
+    let tokens: Vec<Token> = tokens.iter().map(Token::from).collect();
+    assert_eq!(tokens[0], t![-]);
+    assert_eq!(tokens[1].as_str(), "12i64");
+    assert_eq!(true.to_token_stream().len(), 1);
+}
+
 #[test]
 fn test_literal_traits() {
     use crate::util::*;
@@ -0,0 +1,106 @@
+//! Ad-hoc ordered choice between parsed types, for alternatives that don't warrant deriving
+//! [`Parse`] on a dedicated enum.
+
+use super::*;
+
+macro_rules! choice {
+    ($(#[$attr:meta])* $name:ident { $($variant:ident($ty:ident)),+ $(,)? }) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub enum $name<$($ty),+> {
+            $($variant($ty)),+
+        }
+
+        impl<$($ty: ToTokens),+> ToTokens for $name<$($ty),+> {
+            fn to_token_stream(&self) -> TokenStream {
+                match self {
+                    $($name::$variant(value) => value.to_token_stream()),+
+                }
+            }
+        }
+
+        impl<$($ty: Spanned),+> Spanned for $name<$($ty),+> {
+            fn span(&self) -> Span {
+                match self {
+                    $($name::$variant(value) => value.span()),+
+                }
+            }
+        }
+
+        impl<$($ty: Parse),+> Parse for $name<$($ty),+> {
+            /// Tries each alternative in order on a fork of `input`, committing to the first one
+            /// that parses. If none do, the errors of the alternatives that made the most
+            /// progress are merged.
+            fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
+                let mut alternatives = Alternatives::new();
+                $(
+                    if let Some(value) = alternatives.attempt(input, |input| input.parse::<$ty>()) {
+                        return Ok($name::$variant(value));
+                    }
+                )+
+                Err(alternatives.into_error(input))
+            }
+
+            /// Lists the descriptions of all of the alternatives, i.e. "ident or `,`".
+            fn description() -> InStr {
+                parsing::or_list(&[$($ty::description().to_string()),+]).into()
+            }
+        }
+    };
+}
+
+choice! {
+    /// Either an `L` or, if that fails to parse, an `R`.
+    Either { Left(L), Right(R) }
+}
+
+choice! {
+    /// The first of `A`, `B` or `C` that parses.
+    OneOf3 { A(A), B(B), C(C) }
+}
+
+choice! {
+    /// The first of `A`, `B`, `C` or `D` that parses.
+    OneOf4 { A(A), B(B), C(C), D(D) }
+}
+
+choice! {
+    /// The first of `A`, `B`, `C`, `D` or `E` that parses.
+    OneOf5 { A(A), B(B), C(C), D(D), E(E) }
+}
+
+choice! {
+    /// The first of `A`, `B`, `C`, `D`, `E` or `F` that parses.
+    OneOf6 { A(A), B(B), C(C), D(D), E(E), F(F) }
+}
+
+#[test]
+fn test_parse_either() {
+    let tokens: TokenStream = "a, b".parse().unwrap();
+    let mut input = tokens.to_parser();
+    let ident = input.parse::<Either<Ident, punct::Comma>>().unwrap();
+    assert!(matches!(ident, Either::Left(ident) if ident == "a"));
+    assert_eq!(ident.span().source_text(), Some("a"));
+    let comma = input.parse::<Either<Ident, punct::Comma>>().unwrap();
+    assert!(matches!(comma, Either::Right(_)));
+    assert_eq!(comma.to_token_stream().len(), 1);
+    assert_eq!(<Either<Ident, punct::Comma>>::description(), "ident or `,`");
+    // ordered: the first alternative wins even if a later one would consume more
+    let tokens: TokenStream = "a b".parse().unwrap();
+    let (parsed, remaining) = parse_compact::<Either<Ident, (Ident, Ident)>>(&tokens).unwrap();
+    assert!(matches!(parsed, Either::Left(_)));
+    assert_eq!(remaining.len(), 1);
+}
+
+#[test]
+fn test_parse_one_of_errors() {
+    type Choice = OneOf3<(Ident, punct::Colon), (Ident, punct::Semi), punct::Comma>;
+    let tokens: TokenStream = "a b".parse().unwrap();
+    let err = parse::<Choice>(&tokens).unwrap_err();
+    // the comma alternative failed earlier, so only the furthest failures are reported
+    assert_eq!(err.messages.len(), 1);
+    assert_eq!(err.messages[0], "expected `:` or `;`, found `b`");
+    let tokens: TokenStream = "a;".parse().unwrap();
+    assert!(matches!(parse::<Choice>(&tokens), Ok(OneOf3::B(_))));
+    assert_eq!(Choice::description(), "ident, ident or `,`");
+}
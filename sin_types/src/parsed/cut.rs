@@ -0,0 +1,82 @@
+use super::*;
+
+/// Consumes nothing, but commits to the alternative being parsed, see [`Parser::commit`].
+///
+/// ```ignore
+/// #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+/// enum Item {
+///     // once `fn` was parsed, errors in the rest of the signature are reported as-is
+///     Fn(Keyword, Cut, Ident, Paren<Rep<TypedField>>),
+///     Field(TypedField),
+/// }
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+pub struct Cut;
+
+impl Spanned for Cut {
+    fn span(&self) -> Span {
+        Span::call_site()
+    }
+}
+
+impl ToTokens for Cut {
+    fn to_token_stream(&self) -> TokenStream {
+        TokenStream::new()
+    }
+}
+
+impl Parse for Cut {
+    fn parse<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        input.commit();
+        Ok(Cut)
+    }
+}
+
+#[test]
+fn test_cut_in_choices() {
+    type Field = (Ident, Cut, punct::Colon, Ident);
+    let tokens: TokenStream = "a, b".parse().unwrap();
+    let err = parse_compact::<Either<Field, (Ident, punct::Comma)>>(&tokens).unwrap_err();
+    assert_eq!(err.messages.len(), 1);
+    assert_eq!(err.messages[0], "expected `:`, found `,`");
+    let uncut =
+        parse_compact::<Either<(Ident, punct::Colon, Ident), (Ident, punct::Comma)>>(&tokens);
+    assert!(matches!(uncut, Ok((Either::Right(_), _))));
+    let err = parse_compact::<Option<Field>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `:`, found `,`");
+    let (none, _) = parse_compact::<Option<(Ident, punct::Colon)>>(&tokens).unwrap();
+    assert!(none.is_none());
+    let tokens: TokenStream = "a: b c".parse().unwrap();
+    let err = parse_compact::<Vec<Field>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `:`, found end of tokens");
+    let tokens: TokenStream = "a: b, c".parse().unwrap();
+    let err = parse_compact::<Rep<Field, punct::Comma, true>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `:`, found end of tokens");
+    let (rep, _) =
+        parse_compact::<Rep<(Ident, punct::Colon, Ident), punct::Comma, true>>(&tokens).unwrap();
+    assert_eq!(rep.len(), 1);
+}
+
+#[test]
+fn test_cut_is_local() {
+    let tokens: TokenStream = "a b".parse().unwrap();
+    let mut input = tokens.to_parser();
+    // the inner choice is committed and doesn't try `Ident`, but the outer one may still try
+    // its other alternatives
+    type Nested = Either<Either<(Ident, Cut, punct::Colon), Ident>, (Ident, Ident)>;
+    assert!(matches!(input.parse::<Nested>(), Ok(Either::Right(_))));
+    // committing within a successful alternative doesn't commit the enclosing one
+    let mut input = tokens.to_parser();
+    assert!(input.parse::<Either<(Ident, Cut), Ident>>().is_ok());
+    assert!(!input.is_committed());
+    input.parse::<Cut>().unwrap();
+    assert!(input.is_committed());
+    // a memoized rule replays its commit
+    let mut input = tokens.to_parser();
+    input.memoize();
+    assert!(input
+        .clone()
+        .try_parse::<(Ident, Cut, punct::Colon)>()
+        .is_err());
+    assert!(input.try_parse::<(Ident, Cut, punct::Colon)>().is_err());
+}
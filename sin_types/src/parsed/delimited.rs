@@ -0,0 +1,140 @@
+use super::*;
+use core::{marker::PhantomData, ops::Deref};
+use delimiter::DelimiterKind;
+
+/// A `T` within a [`Group`] delimited by `D`, such as `(a, b)` for
+/// `Delimited<delimiter::Paren, Rep<Ident, punct::Comma>>`.
+///
+/// Parsing consumes a single [`Group`] with the right [`Delimiter`] and parses its content
+/// entirely as `T`. Errors at the end of the content point at `span_close`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Delimited<D: DelimiterKind, T: Parse> {
+    pub content: T,
+    /// The [`Span`] of the whole [`Group`], including its delimiters.
+    pub span: Span,
+    pub span_open: Span,
+    pub span_close: Span,
+    _delimiter: PhantomData<D>,
+}
+
+/// A `T` within parentheses, i.e. `( .. )`.
+pub type Paren<T> = Delimited<delimiter::Paren, T>;
+
+/// A `T` within brackets, i.e. `[ .. ]`.
+pub type Bracket<T> = Delimited<delimiter::Bracket, T>;
+
+/// A `T` within braces, i.e. `{ .. }`.
+pub type Brace<T> = Delimited<delimiter::Brace, T>;
+
+impl<D: DelimiterKind, T: Parse> Delimited<D, T> {
+    /// Creates a [`Delimited`] with [`Span::call_site()`] spans.
+    pub fn new(content: T) -> Self {
+        Delimited::new_spanned(Span::call_site(), content)
+    }
+
+    /// Creates a [`Delimited`] whose [`Group`] and delimiters all have the specified [`Span`].
+    pub fn new_spanned(span: Span, content: T) -> Self {
+        Delimited {
+            content,
+            span,
+            span_open: span,
+            span_close: span,
+            _delimiter: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.content
+    }
+}
+
+impl<D: DelimiterKind, T: Parse> Deref for Delimited<D, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.content
+    }
+}
+
+impl<D: DelimiterKind, T: Parse> Spanned for Delimited<D, T> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<D: DelimiterKind, T: Parse> ToTokens for Delimited<D, T> {
+    /// Emits a single [`Group`] with the original delimiter [`Span`]s.
+    fn to_token_stream(&self) -> TokenStream {
+        let group = Group {
+            delimiter: D::DELIMITER,
+            span: self.span,
+            span_open: self.span_open,
+            span_close: self.span_close,
+            content: self.content.to_token_stream(),
+        };
+        TokenTree::Tree(group).to_token_stream()
+    }
+}
+
+impl<D: DelimiterKind, T: Parse> Parse for Delimited<D, T> {
+    fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
+        let group = input.parse_group(D::DELIMITER)?;
+        Ok(Delimited {
+            content: input.parse_nested(&group.content, group.span_close)?,
+            span: group.span,
+            span_open: group.span_open,
+            span_close: group.span_close,
+            _delimiter: PhantomData,
+        })
+    }
+
+    fn description() -> InStr {
+        TokenPattern::Delimiter(Pattern::Specific(D::DELIMITER))
+            .to_string()
+            .into()
+    }
+}
+
+#[test]
+fn test_parse_delimited() {
+    let tokens: TokenStream = "(a, b) [c] {}".parse().unwrap();
+    let mut input = tokens.to_parser();
+    let paren = input.parse::<Paren<Rep<Ident, punct::Comma>>>().unwrap();
+    assert_eq!(paren.len(), 2);
+    assert_eq!(paren.span.source_text(), Some("(a, b)"));
+    assert_eq!(paren.span_open.source_text(), Some("("));
+    assert_eq!(paren.span_close.source_text(), Some(")"));
+    let bracket = input.parse::<Bracket<Ident>>().unwrap();
+    assert_eq!(bracket.content, "c");
+    let brace = input.parse::<Brace<Nothing>>().unwrap();
+    assert_eq!(brace.span.source_text(), Some("{}"));
+    let mut round_trip = paren.to_token_stream();
+    round_trip.extend(bracket.to_token_stream());
+    round_trip.extend(brace.to_token_stream());
+    let mut input = round_trip.to_parser();
+    assert_eq!(
+        input.parse::<Paren<Rep<Ident, punct::Comma>>>().unwrap(),
+        paren
+    );
+    assert_eq!(input.parse::<Bracket<Ident>>().unwrap(), bracket);
+    assert_eq!(input.parse::<Brace<Nothing>>().unwrap(), brace);
+    let built = Paren::new(Ident::new("x")).to_token_stream();
+    assert_eq!(parse::<Paren<Ident>>(built).unwrap().content, "x");
+}
+
+#[test]
+fn test_parse_delimited_errors() {
+    let tokens: TokenStream = "[a]".parse().unwrap();
+    let err = parse::<Paren<Ident>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected parenthesis, found `[]`");
+    assert_eq!(err.messages[0].span.source_text(), Some("[a]"));
+    let tokens: TokenStream = "(a b)".parse().unwrap();
+    let err = parse::<Paren<Ident>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected nothing, found `b`");
+    assert_eq!(err.messages[0].span.source_text(), Some("b"));
+    let tokens: TokenStream = "{}".parse().unwrap();
+    let err = parse::<Brace<Rep1<punct::Comma>>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected at least 1 `,`");
+    assert_eq!(err.messages[0].span.source_text(), Some("}"));
+    assert_eq!(Paren::<Ident>::description(), "parenthesis");
+}
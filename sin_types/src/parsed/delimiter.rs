@@ -0,0 +1,34 @@
+//! Type-level [`Delimiter`]s, used as the `D` parameter of [`Delimited`](crate::Delimited).
+
+use crate::Delimiter;
+
+/// A type-level [`Delimiter`].
+pub trait DelimiterKind:
+    Copy + Clone + PartialEq + Eq + PartialOrd + Ord + core::hash::Hash + core::fmt::Debug + Default
+{
+    const DELIMITER: Delimiter;
+}
+
+/// `( .. )`
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Paren;
+
+impl DelimiterKind for Paren {
+    const DELIMITER: Delimiter = Delimiter::Paren;
+}
+
+/// `[ .. ]`
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Bracket;
+
+impl DelimiterKind for Bracket {
+    const DELIMITER: Delimiter = Delimiter::Bracket;
+}
+
+/// `{ .. }`
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Brace;
+
+impl DelimiterKind for Brace {
+    const DELIMITER: Delimiter = Delimiter::Brace;
+}
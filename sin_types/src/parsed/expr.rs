@@ -0,0 +1,289 @@
+//! A precedence-climbing (Pratt) parser for expressions built from atoms and operators.
+
+use super::*;
+use core::marker::PhantomData;
+
+/// How operators of the same binding power group when chained, i.e. whether `a - b - c` is
+/// parsed as `(a - b) - c` or as `a - (b - c)`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// The prefix, infix and postfix operators of an [`Expr`], and how tightly they bind.
+///
+/// Operators with a higher binding power bind more tightly, so `*` should have a higher power
+/// than `+`. A pattern may be both a prefix and an infix operator, like `-`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct OperatorTable {
+    prefix: Vec<(TokenPattern, u8)>,
+    infix: Vec<(TokenPattern, u8, Associativity)>,
+    postfix: Vec<(TokenPattern, u8)>,
+}
+
+impl OperatorTable {
+    pub fn new() -> Self {
+        OperatorTable::default()
+    }
+
+    /// Adds a prefix operator, i.e. `-` in `-a`.
+    pub fn prefix(mut self, pattern: TokenPattern, power: u8) -> Self {
+        self.prefix.push((pattern, power));
+        self
+    }
+
+    /// Adds an infix operator, i.e. `+` in `a + b`.
+    pub fn infix(mut self, pattern: TokenPattern, power: u8, associativity: Associativity) -> Self {
+        self.infix.push((pattern, power, associativity));
+        self
+    }
+
+    /// Adds a postfix operator, i.e. `?` in `a?`.
+    pub fn postfix(mut self, pattern: TokenPattern, power: u8) -> Self {
+        self.postfix.push((pattern, power));
+        self
+    }
+}
+
+/// A type-level [`OperatorTable`], used as the `O` parameter of [`Expr`].
+pub trait Operators:
+    Copy + Clone + PartialEq + Eq + PartialOrd + Ord + core::hash::Hash + core::fmt::Debug + Default
+{
+    fn operators() -> OperatorTable;
+}
+
+/// An expression of `A` atoms combined by the operators of `O`, parsed by precedence climbing.
+///
+/// ```ignore
+/// #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+/// struct Arithmetic;
+///
+/// impl Operators for Arithmetic {
+///     fn operators() -> OperatorTable {
+///         OperatorTable::new()
+///             .prefix(pat![-], 3)
+///             .infix(pat![+], 1, Associativity::Left)
+///             .infix(pat![*], 2, Associativity::Left)
+///     }
+/// }
+///
+/// // parsed as `(-a) + (b * c)`
+/// let expr = parse::<Expr<Ident, Arithmetic>>(code!(-a + b * c))?;
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Expr<A: Parse, O: Operators> {
+    pub kind: ExprKind<A, O>,
+    _operators: PhantomData<O>,
+}
+
+/// The shape of an [`Expr`]. Operators are kept as the [`TokenTree`] they were parsed from.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum ExprKind<A: Parse, O: Operators> {
+    Atom(A),
+    Prefix {
+        op: TokenTree,
+        expr: Box<Expr<A, O>>,
+    },
+    Infix {
+        lhs: Box<Expr<A, O>>,
+        op: TokenTree,
+        rhs: Box<Expr<A, O>>,
+    },
+    Postfix {
+        expr: Box<Expr<A, O>>,
+        op: TokenTree,
+    },
+}
+
+impl<A: Parse, O: Operators> Expr<A, O> {
+    pub fn new(kind: ExprKind<A, O>) -> Self {
+        Expr {
+            kind,
+            _operators: PhantomData,
+        }
+    }
+
+    pub fn into_kind(self) -> ExprKind<A, O> {
+        self.kind
+    }
+
+    fn parse_with<'a, I: Default + Clone>(
+        input: &mut Parser<'a, I>,
+        table: &OperatorTable,
+        min_power: u32,
+    ) -> ParseResult<Self> {
+        let mut alternatives = Alternatives::new();
+        let prefix = table
+            .prefix
+            .iter()
+            .find(|(pattern, _)| alternatives.peek(input, *pattern));
+        let mut lhs = if let Some((_, power)) = prefix {
+            let op = input.parse::<TokenTree>()?;
+            let expr = Self::parse_with(input, table, *power as u32 * 2)?;
+            Self::new(ExprKind::Prefix {
+                op,
+                expr: Box::new(expr),
+            })
+        } else {
+            let Some(atom) = alternatives.attempt(input, A::parse) else {
+                return Err(alternatives.into_error(input));
+            };
+            Self::new(ExprKind::Atom(atom))
+        };
+        loop {
+            let postfix = table
+                .postfix
+                .iter()
+                .find(|(pattern, _)| input.peek_matches(*pattern));
+            if let Some((_, power)) = postfix {
+                if (*power as u32 * 2) < min_power {
+                    break;
+                }
+                let op = input.parse::<TokenTree>()?;
+                lhs = Self::new(ExprKind::Postfix {
+                    expr: Box::new(lhs),
+                    op,
+                });
+                continue;
+            }
+            let infix = table
+                .infix
+                .iter()
+                .find(|(pattern, ..)| input.peek_matches(*pattern));
+            let Some((_, power, associativity)) = infix else {
+                break;
+            };
+            let power = *power as u32 * 2;
+            if power < min_power {
+                break;
+            }
+            let op = input.parse::<TokenTree>()?;
+            // left associative operators don't accept another of their own power on the right
+            let rhs_power = match associativity {
+                Associativity::Left => power + 1,
+                Associativity::Right => power,
+            };
+            let rhs = Self::parse_with(input, table, rhs_power)?;
+            lhs = Self::new(ExprKind::Infix {
+                lhs: Box::new(lhs),
+                op,
+                rhs: Box::new(rhs),
+            });
+        }
+        Ok(lhs)
+    }
+}
+
+impl<A: Parse, O: Operators> From<ExprKind<A, O>> for Expr<A, O> {
+    fn from(kind: ExprKind<A, O>) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl<A: Parse, O: Operators> ToTokens for Expr<A, O> {
+    fn to_token_stream(&self) -> TokenStream {
+        let mut tokens = TokenStream::new();
+        match &self.kind {
+            ExprKind::Atom(atom) => tokens.extend(atom.to_token_stream()),
+            ExprKind::Prefix { op, expr } => {
+                tokens.push(op.clone());
+                tokens.extend(expr.to_token_stream());
+            }
+            ExprKind::Infix { lhs, op, rhs } => {
+                tokens.extend(lhs.to_token_stream());
+                tokens.push(op.clone());
+                tokens.extend(rhs.to_token_stream());
+            }
+            ExprKind::Postfix { expr, op } => {
+                tokens.extend(expr.to_token_stream());
+                tokens.push(op.clone());
+            }
+        }
+        tokens
+    }
+}
+
+impl<A: Parse, O: Operators> Spanned for Expr<A, O> {
+    /// Returns the [`Span`] of the whole expression, joined across operators and operands.
+    fn span(&self) -> Span {
+        match &self.kind {
+            ExprKind::Atom(atom) => atom.span(),
+            ExprKind::Prefix { op, expr } => __private::join_spans(&[op.span(), expr.span()]),
+            ExprKind::Infix { lhs, rhs, .. } => __private::join_spans(&[lhs.span(), rhs.span()]),
+            ExprKind::Postfix { expr, op } => __private::join_spans(&[expr.span(), op.span()]),
+        }
+    }
+}
+
+impl<A: Parse, O: Operators> Parse for Expr<A, O> {
+    fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
+        Self::parse_with(input, &O::operators(), 0)
+    }
+
+    fn description() -> InStr {
+        A::description()
+    }
+}
+
+#[cfg(test)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+struct Arithmetic;
+
+#[cfg(test)]
+impl Operators for Arithmetic {
+    fn operators() -> OperatorTable {
+        OperatorTable::new()
+            .prefix(pat![-], 4)
+            .infix(pat![+], 1, Associativity::Left)
+            .infix(pat![-], 1, Associativity::Left)
+            .infix(pat![*], 2, Associativity::Left)
+            .infix(pat![^], 3, Associativity::Right)
+            .postfix(pat![?], 5)
+    }
+}
+
+#[cfg(test)]
+fn grouped(expr: &Expr<Ident, Arithmetic>) -> String {
+    let op = |op: &TokenTree| Token::from(op.clone()).to_string();
+    match &expr.kind {
+        ExprKind::Atom(atom) => atom.ident.to_string(),
+        ExprKind::Prefix { op: o, expr } => format!("({}{})", op(o), grouped(expr)),
+        ExprKind::Infix { lhs, op: o, rhs } => {
+            format!("({} {} {})", grouped(lhs), op(o), grouped(rhs))
+        }
+        ExprKind::Postfix { expr, op: o } => format!("({}{})", grouped(expr), op(o)),
+    }
+}
+
+#[test]
+fn test_parse_expr_precedence() {
+    let cases = [
+        ("a + b * c - d", "((a + (b * c)) - d)"),
+        ("a - b - c", "((a - b) - c)"),
+        ("a ^ b ^ c", "(a ^ (b ^ c))"),
+        ("-a * b", "((-a) * b)"),
+        ("-a? ^ b", "((-(a?)) ^ b)"),
+        ("a * -b + c", "((a * (-b)) + c)"),
+        ("- - a", "(-(-a))"),
+    ];
+    for (source, expected) in cases {
+        let tokens: TokenStream = source.parse().unwrap();
+        let expr = parse::<Expr<Ident, Arithmetic>>(&tokens).unwrap();
+        assert_eq!(grouped(&expr), expected);
+        assert_eq!(expr.span().source_text(), Some(source));
+        let round_trip = parse::<Expr<Ident, Arithmetic>>(expr.to_token_stream()).unwrap();
+        assert_eq!(round_trip, expr);
+    }
+}
+
+#[test]
+fn test_parse_expr_errors() {
+    let tokens: TokenStream = "a + , b".parse().unwrap();
+    let err = parse::<Expr<Ident, Arithmetic>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `-` or ident, found `,`");
+    let tokens: TokenStream = "a + b, c".parse().unwrap();
+    let (expr, remaining) = parse_compact::<Expr<Ident, Arithmetic>>(&tokens).unwrap();
+    assert_eq!(grouped(&expr), "(a + b)");
+    assert_eq!(remaining.len(), 2);
+}
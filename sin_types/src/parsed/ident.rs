@@ -22,7 +22,7 @@ impl Ident {
     }
 }
 
-impl<'a, T: AsRef<str>> PartialEq<T> for Ident {
+impl<T: AsRef<str>> PartialEq<T> for Ident {
     fn eq(&self, other: &T) -> bool {
         self.ident == other.as_ref()
     }
@@ -52,17 +52,22 @@ impl Parse for Ident {
             return Err(ParseError::new().expected_token(pat![!ident], None, input.span()));
         };
         let TokenTree::Leaf(token, span) = token_tree else {
+            let span = token_tree.span();
             return Err(ParseError::new().expected_token(
                 pat![!ident],
                 Some(token_tree.into()),
-                input.span(),
+                span,
             ));
         };
         let Token::Ident(ident) = token else {
-            return Err(ParseError::new().expected_token(pat![!ident], Some(token), input.span()));
+            return Err(ParseError::new().expected_token(pat![!ident], Some(token), span));
         };
         Ok(Ident { ident, span })
     }
+
+    fn description() -> InStr {
+        pat![!ident].to_string().into()
+    }
 }
 
 impl ToTokens for Ident {
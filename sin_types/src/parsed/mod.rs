@@ -9,7 +9,28 @@ pub use nothing::*;
 mod rep;
 pub use rep::*;
 
+mod delimited;
+pub use delimited::*;
+
+mod std_impls;
+
+mod choice;
+pub use choice::*;
+
+mod expr;
+pub use expr::*;
+
 mod no_op;
 pub use no_op::*;
 
+mod cut;
+pub use cut::*;
+
+mod recover;
+pub use recover::*;
+
 pub mod punct;
+
+pub mod trailing;
+
+pub mod delimiter;
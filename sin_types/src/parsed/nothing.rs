@@ -21,7 +21,7 @@ impl Parse for Nothing {
             return Ok(Nothing {});
         };
         let span = token.span();
-        return Err(ParseError::new().expected_token(pat![], Some(token.into()), span));
+        Err(ParseError::new().expected_token(pat![], Some(token.into()), span))
     }
 }
 
@@ -30,8 +30,7 @@ fn test_parse_nothing() {
     let empty = TokenStream::new();
     let mut input: Parser = empty.to_parser();
     assert!(input.parse::<Nothing>().is_ok());
-    let tokens: TokenStream =
-        (&[TokenTree::Leaf((t![some_token]).into(), Span::call_site())][..]).into();
+    let tokens: TokenStream = (&[TokenTree::Leaf(t![some_token], Span::call_site())][..]).into();
     let mut input: Parser = tokens.to_parser();
     assert!(input.parse::<Nothing>().is_err());
 }
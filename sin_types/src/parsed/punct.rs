@@ -41,10 +41,14 @@ macro_rules! define_parsed_punct {
 				};
 				let token: Token = token_tree.clone().into();
 				match token {
-					t![$($tt)+] => Ok($ident { span: input.span() }),
+					t![$($tt)+] => Ok($ident { span: token_tree.span() }),
 					_ => Err($crate::parsing::ParseError::new().expected_token(pat![$($tt)+], Some(token), token_tree.span())),
 				}
 			}
+
+			fn description() -> InStr {
+				pat![$($tt)+].to_string().into()
+			}
 		}
     };
 }
@@ -96,3 +100,17 @@ define_parsed_punct!(Pound, [#]);
 define_parsed_punct!(Dollar, [$]);
 define_parsed_punct!(Question, [?]);
 define_parsed_punct!(Tilde, [~]);
+
+#[test]
+fn test_parse_punct_span() {
+    let tokens: TokenStream = "a += b".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    input.next();
+    let plus_eq = input.parse::<PlusEq>().unwrap();
+    // the span of the parsed token, not of the whole input
+    assert_eq!(plus_eq.span().source_text(), Some("+="));
+    assert_eq!(
+        plus_eq.to_token_stream().as_slice()[0].span(),
+        plus_eq.span()
+    );
+}
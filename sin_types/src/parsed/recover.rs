@@ -0,0 +1,151 @@
+use super::*;
+
+/// A `T`, or the tokens skipped to recover from failing to parse one, up to and including the
+/// next `S`. See [`Parser::parse_recover`].
+///
+/// ```ignore
+/// // reports an error for every malformed statement, not just the first one
+/// let statements = parse::<Vec<Recover<Statement, punct::Semi>>>(tokens)?;
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Recover<T: Parse, S: Parse> {
+    Parsed(T),
+    Skipped {
+        /// Why a `T` failed to parse, which was also recorded in [`Parser::errors`].
+        error: ParseError,
+        /// The tokens skipped before `sync`.
+        skipped: TokenStream,
+        /// The `S` that parsing resumed after, or `None` if the end of the input was reached.
+        sync: Option<S>,
+    },
+}
+
+impl<T: Parse, S: Parse> Recover<T, S> {
+    /// Returns the parsed `T`, if any.
+    pub fn parsed(&self) -> Option<&T> {
+        match self {
+            Recover::Parsed(parsed) => Some(parsed),
+            Recover::Skipped { .. } => None,
+        }
+    }
+
+    /// Returns the error recovered from, if any.
+    pub fn error(&self) -> Option<&ParseError> {
+        match self {
+            Recover::Parsed(_) => None,
+            Recover::Skipped { error, .. } => Some(error),
+        }
+    }
+}
+
+impl<T: Parse, S: Parse> ToTokens for Recover<T, S> {
+    /// Emits the parsed `T`, or the skipped tokens followed by the `S`.
+    fn to_token_stream(&self) -> TokenStream {
+        match self {
+            Recover::Parsed(parsed) => parsed.to_token_stream(),
+            Recover::Skipped { skipped, sync, .. } => {
+                let mut tokens = skipped.clone();
+                tokens.extend(sync.to_token_stream());
+                tokens
+            }
+        }
+    }
+}
+
+impl<T: Parse, S: Parse> Spanned for Recover<T, S> {
+    /// Returns the [`Span`] of the parsed `T`, or the joined [`Span`] of the skipped tokens and
+    /// the `S`, falling back to where the error was reported if nothing was skipped.
+    fn span(&self) -> Span {
+        match self {
+            Recover::Parsed(parsed) => parsed.span(),
+            Recover::Skipped {
+                error,
+                skipped,
+                sync,
+            } => {
+                let mut spans: Vec<Span> = skipped.iter().map(|token| token.span()).collect();
+                spans.push(sync.span());
+                let span = __private::join_spans(&spans);
+                match error.messages.first() {
+                    Some(message) if span.is_call_site() => message.span,
+                    _ => span,
+                }
+            }
+        }
+    }
+}
+
+impl<T: Parse, S: Parse> Parse for Recover<T, S> {
+    fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
+        Ok(input.parse_recover())
+    }
+
+    fn description() -> InStr {
+        T::description()
+    }
+}
+
+#[cfg(test)]
+type Statement = (Ident, punct::Eq, Ident, punct::Semi);
+
+#[test]
+fn test_parse_recover() {
+    let tokens: TokenStream = "a = b; c = ; d = e; f g; h = i;".parse().unwrap();
+    let mut input = tokens.to_parser();
+    let statements = input
+        .parse::<Vec<Recover<Statement, punct::Semi>>>()
+        .unwrap();
+    assert_eq!(statements.len(), 5);
+    assert_eq!(statements.iter().filter_map(Recover::parsed).count(), 3);
+    assert_eq!(input.errors().messages.len(), 2);
+    assert_eq!(input.errors().messages[0], "expected ident, found `;`");
+    assert_eq!(input.errors().messages[1], "expected `=`, found `g`");
+    let skipped = &statements[1];
+    assert_eq!(skipped.error().unwrap().messages.len(), 1);
+    assert_eq!(skipped.span().source_text(), Some("c = ;"));
+    assert_eq!(statements.to_token_stream().len(), tokens.len());
+    assert_eq!(input.take_errors().messages.len(), 2);
+    assert!(input.errors().messages.is_empty());
+    // every recovered error is reported
+    let err = parse::<Vec<Recover<Statement, punct::Semi>>>(&tokens).unwrap_err();
+    assert_eq!(err.messages.len(), 2);
+    // or returned along with the partially parsed tree
+    let (statements, errors) =
+        parse_recovering::<Vec<Recover<Statement, punct::Semi>>>(&tokens).unwrap();
+    assert_eq!(statements.len(), 5);
+    assert_eq!(errors.messages.len(), 2);
+    let tokens: TokenStream = "a = ; b c".parse().unwrap();
+    let err = parse::<(Recover<Statement, punct::Semi>, Ident)>(&tokens).unwrap_err();
+    assert_eq!(err.messages.len(), 2);
+    assert_eq!(err.messages[0], "expected ident, found `;`");
+    assert_eq!(err.messages[1].found.as_ref().unwrap().to_string(), "c");
+}
+
+#[test]
+fn test_parse_recover_in_groups() {
+    type Block = Brace<Vec<Recover<Statement, punct::Semi>>>;
+    let tokens: TokenStream = "{ a = ; b } { c = d; }".parse().unwrap();
+    let mut input = tokens.to_parser();
+    let first = input.parse::<Block>().unwrap();
+    // skipped to the end of the group without a `;`
+    assert!(matches!(
+        &first.content[1],
+        Recover::Skipped { sync: None, .. }
+    ));
+    let second = input.parse::<Block>().unwrap();
+    assert!(second.content[0].parsed().is_some());
+    assert_eq!(input.errors().messages.len(), 2);
+    // errors in alternatives that are backtracked out of are discarded
+    let tokens: TokenStream = "{ a = ; } b".parse().unwrap();
+    let mut input = tokens.to_parser();
+    let either = input.parse::<Either<(Block, punct::Comma), (TokenTree, Ident)>>();
+    assert!(matches!(either, Ok(Either::Right(_))));
+    assert!(input.errors().messages.is_empty());
+    // memoized rules replay the errors they recovered from
+    let mut input = tokens.to_parser();
+    input.memoize();
+    input.parse::<Option<(Block, punct::Comma)>>().unwrap();
+    assert!(input.errors().messages.is_empty());
+    input.parse::<Block>().unwrap();
+    assert_eq!(input.errors().messages.len(), 1);
+}
@@ -1,53 +1,270 @@
 use super::*;
+use core::marker::PhantomData;
+use trailing::TrailingPolicy;
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
-pub struct Rep<T: Parse, S: Parse = NoOp, const COMPACT: bool = false> {
+/// A repetition of `T`s separated by `S`s, such as `a, b, c`.
+///
+/// When `COMPACT` is `true`, parsing stops at the first `T` or `S` that does not parse rather
+/// than failing, leaving the rest of the input for whatever follows. `P` determines whether
+/// the last item may be followed by a separator; see [`trailing`].
+///
+/// Parsing fails unless there are between `MIN` and `MAX` items (inclusive). In `COMPACT` mode,
+/// parsing instead stops once there are `MAX` items. Constructing a [`Rep`] whose `MIN` is
+/// greater than its `MAX` fails to compile.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Rep<
+    T: Parse,
+    S: Parse = NoOp,
+    const COMPACT: bool = false,
+    P: TrailingPolicy = trailing::Optional,
+    const MIN: usize = 0,
+    const MAX: usize = { usize::MAX },
+> {
     items: Vec<T>,
     seps: Vec<S>,
-    span: Span,
+    span: SpanCache,
+    _policy: PhantomData<P>,
 }
 
-impl<T: Parse, S: Parse, const COMPACT: bool> Spanned for Rep<T, S, COMPACT> {
+/// The joined [`Span`] of the items and separators of a [`Rep`], kept up to date by its
+/// editing methods so that [`Spanned::span`] doesn't join them on every call. It is `None`
+/// once the items may have been modified in place (i.e. via [`Rep::iter_mut`]).
+///
+/// Since it is derived from the items, it is ignored when comparing and hashing [`Rep`]s.
+#[derive(Copy, Clone, Debug)]
+struct SpanCache(Option<Span>);
+
+impl PartialEq for SpanCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for SpanCache {}
+
+impl PartialOrd for SpanCache {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SpanCache {
+    fn cmp(&self, _: &Self) -> core::cmp::Ordering {
+        core::cmp::Ordering::Equal
+    }
+}
+
+impl core::hash::Hash for SpanCache {
+    fn hash<H: core::hash::Hasher>(&self, _: &mut H) {}
+}
+
+/// A [`Rep`] of one or more `T`s separated by `S`s.
+pub type Rep1<T, S = NoOp> = Rep<T, S, false, trailing::Optional, 1>;
+
+/// An item of a [`Rep`] along with the separator following it, if any.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Pair<T, S> {
+    Punctuated(T, S),
+    End(T),
+}
+
+impl<T, S> Pair<T, S> {
+    pub fn new(value: T, separator: Option<S>) -> Self {
+        match separator {
+            Some(separator) => Pair::Punctuated(value, separator),
+            None => Pair::End(value),
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        match self {
+            Pair::Punctuated(value, _) | Pair::End(value) => value,
+        }
+    }
+
+    pub fn value_mut(&mut self) -> &mut T {
+        match self {
+            Pair::Punctuated(value, _) | Pair::End(value) => value,
+        }
+    }
+
+    pub fn separator(&self) -> Option<&S> {
+        match self {
+            Pair::Punctuated(_, separator) => Some(separator),
+            Pair::End(_) => None,
+        }
+    }
+
+    pub fn into_value(self) -> T {
+        match self {
+            Pair::Punctuated(value, _) | Pair::End(value) => value,
+        }
+    }
+
+    pub fn into_tuple(self) -> (T, Option<S>) {
+        match self {
+            Pair::Punctuated(value, separator) => (value, Some(separator)),
+            Pair::End(value) => (value, None),
+        }
+    }
+}
+
+impl<
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > Default for Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    fn default() -> Self {
+        const { assert!(MIN <= MAX, "the `MIN` of a `Rep` cannot exceed its `MAX`") };
+        Rep {
+            items: Vec::new(),
+            seps: Vec::new(),
+            span: SpanCache(Some(Span::call_site())),
+            _policy: PhantomData,
+        }
+    }
+}
+
+impl<
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > Spanned for Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    /// Returns the joined [`Span`] of all of the items and separators, or
+    /// [`Span::call_site()`] if there are none.
     fn span(&self) -> Span {
-        self.span
+        self.span.0.unwrap_or_else(|| self.join_spans())
     }
 }
 
-impl<T: Parse, S: Parse, const COMPACT: bool> ToTokens for Rep<T, S, COMPACT> {
+impl<
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > ToTokens for Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    /// Emits the items interleaved with their separators in their original order, including
+    /// any trailing separator.
     fn to_token_stream(&self) -> TokenStream {
-        todo!()
+        let mut tokens = TokenStream::new();
+        for pair in self.pairs() {
+            tokens.extend(pair.value().to_token_stream());
+            if let Some(sep) = pair.separator() {
+                tokens.extend(sep.to_token_stream());
+            }
+        }
+        tokens
     }
 }
 
-impl<T: Parse, S: Parse, const COMPACT: bool> Parse for Rep<T, S, COMPACT> {
+impl<
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > Parse for Rep<T, S, COMPACT, P, MIN, MAX>
+{
     fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
-        let mut ret = Rep {
-            items: Vec::new(),
-            seps: Vec::new(),
-            span: input.span(),
-        };
+        let mut ret = Rep::new();
         loop {
             if input.peek().is_none() {
                 break;
             }
-            if COMPACT && input.peek_parse::<T>().is_err() {
+            let item = match COMPACT {
+                true => input.try_parse::<T>()?,
+                false => Some(input.parse::<T>()?),
+            };
+            let Some(item) = item else {
+                break;
+            };
+            ret.extend_span(item.span());
+            ret.items.push(item);
+            let full = COMPACT && ret.items.len() == MAX;
+            if !P::REQUIRED && (input.peek().is_none() || full) {
                 break;
             }
-            ret.items.push(input.parse::<T>()?);
-            if input.peek().is_none() {
+            let sep = match COMPACT && !P::REQUIRED {
+                true => input.try_parse::<S>()?,
+                false => Some(input.parse::<S>()?),
+            };
+            let Some(sep) = sep else {
                 break;
+            };
+            let tokens = sep.to_token_stream();
+            // an item that fails after committing isn't a reason for the separator to be trailing
+            let is_trailing = input.peek().is_none()
+                || full
+                || COMPACT && matches!(input.clone().try_parse::<T>(), Ok(None));
+            // separators without tokens (i.e. `NoOp`) are never considered trailing
+            if !P::ALLOWED && is_trailing && !tokens.is_empty() {
+                let text: String = tokens.iter().map(|tt| tt.to_string()).collect();
+                return Err(ParseError::new()
+                    .error(sep.span(), format!("trailing `{text}` not allowed here")));
             }
-            if COMPACT && input.peek_parse::<S>().is_err() {
+            ret.extend_span(sep.span());
+            ret.seps.push(sep);
+            if full {
                 break;
             }
-            ret.seps.push(input.parse::<S>()?);
         }
-        ret.span = ret.span.join(input.span()).unwrap_or(Span::call_site());
-        Ok(ret)
+        let bound = match ret.len() {
+            len if len < MIN => format!("at least {MIN} {}", T::description()),
+            len if len > MAX => format!("at most {MAX} {}", T::description()),
+            _ => return Ok(ret),
+        };
+        let span = match ret.is_empty() {
+            true => input
+                .peek()
+                .map_or(input.span(), |token_tree| token_tree.span()),
+            false => ret.span(),
+        };
+        Err(ParseError::new().error(span, format!("expected {bound}")))
     }
 }
 
-impl<T: Parse, S: Parse> Rep<T, S> {
+impl<
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    pub fn new() -> Self {
+        Rep::default()
+    }
+
+    /// Joins the [`Span`]s of all of the items and separators.
+    fn join_spans(&self) -> Span {
+        let mut spans = Vec::new();
+        for (item, sep) in self.pairs().map(Pair::into_tuple) {
+            spans.push(item.span());
+            spans.extend(sep.map(Spanned::span));
+        }
+        __private::join_spans(&spans)
+    }
+
+    /// Joins `span` into the cached [`Span`] of an item or separator being added.
+    fn extend_span(&mut self, span: Span) {
+        if let Some(cached) = self.span.0 {
+            self.span.0 = Some(__private::join_spans(&[cached, span]));
+        }
+    }
+
     pub fn items(&self) -> &Vec<T> {
         &self.items
     }
@@ -57,12 +274,260 @@ impl<T: Parse, S: Parse> Rep<T, S> {
     }
 
     pub fn punctuated(&self) -> Vec<(&T, Option<&S>)> {
-        let mut ret = Vec::new();
-        let mut seps_iter = self.seps.iter();
-        for item in &self.items {
-            ret.push((item, seps_iter.next()));
+        self.pairs().map(Pair::into_tuple).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns `true` if the last item is followed by a separator.
+    pub fn trailing_separator(&self) -> bool {
+        !self.items.is_empty() && self.seps.len() == self.items.len()
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.items.first()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// Iterates over mutable references to the items, after which [`Spanned::span`] joins the
+    /// spans of the items again until the next [`Rep::pop`] or [`Rep::clear`].
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+        self.span.0 = None;
+        self.items.iter_mut()
+    }
+
+    /// Iterates over the items along with the separators following them.
+    pub fn pairs(&self) -> impl Iterator<Item = Pair<&T, &S>> {
+        let mut seps = self.seps.iter();
+        self.items
+            .iter()
+            .map(move |item| Pair::new(item, seps.next()))
+    }
+
+    /// Converts this [`Rep`] into an iterator over its items along with the separators
+    /// following them.
+    pub fn into_pairs(self) -> impl Iterator<Item = Pair<T, S>> {
+        let mut seps = self.seps.into_iter();
+        self.items
+            .into_iter()
+            .map(move |item| Pair::new(item, seps.next()))
+    }
+
+    /// Appends an item, which must either be the first item or follow a trailing separator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Rep`] is not empty and has no trailing separator.
+    pub fn push_value(&mut self, value: T) {
+        assert!(
+            self.is_empty() || self.trailing_separator(),
+            "Rep::push_value: the last item must be followed by a separator"
+        );
+        self.extend_span(value.span());
+        self.items.push(value);
+    }
+
+    /// Appends a trailing separator after the last item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this [`Rep`] is empty or already has a trailing separator.
+    pub fn push_punct(&mut self, separator: S) {
+        assert!(
+            !self.is_empty() && !self.trailing_separator(),
+            "Rep::push_punct: a separator must follow an item"
+        );
+        self.extend_span(separator.span());
+        self.seps.push(separator);
+    }
+
+    /// Removes the last item along with its trailing separator, if any.
+    pub fn pop(&mut self) -> Option<Pair<T, S>> {
+        let sep = match self.trailing_separator() {
+            true => self.seps.pop(),
+            false => None,
+        };
+        let pair = self.items.pop().map(|item| Pair::new(item, sep));
+        self.span.0 = Some(self.join_spans());
+        pair
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.seps.clear();
+        self.span.0 = Some(Span::call_site());
+    }
+}
+
+impl<
+        T: Parse,
+        S: Parse + Default,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    /// Appends an item, first appending a default separator after the last item if needed.
+    pub fn push(&mut self, value: T) {
+        if !self.is_empty() && !self.trailing_separator() {
+            self.push_punct(S::default());
         }
-        ret
+        self.extend_span(value.span());
+        self.items.push(value);
+    }
+
+    /// Inserts an item at `index`, followed by a default separator unless it is inserted at
+    /// the end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of items.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len(), "Rep::insert: index out of range");
+        if index == self.len() {
+            return self.push(value);
+        }
+        let separator = S::default();
+        self.extend_span(value.span());
+        self.extend_span(separator.span());
+        self.items.insert(index, value);
+        self.seps.insert(index, separator);
+    }
+}
+
+impl<
+        T: Parse,
+        S: Parse + Default,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > Extend<T> for Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > Extend<Pair<T, S>> for Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    /// Appends each [`Pair`], which must all have separators except for the last.
+    fn extend<I: IntoIterator<Item = Pair<T, S>>>(&mut self, iter: I) {
+        for pair in iter {
+            let (value, sep) = pair.into_tuple();
+            self.push_value(value);
+            if let Some(sep) = sep {
+                self.push_punct(sep);
+            }
+        }
+    }
+}
+
+impl<
+        T: Parse,
+        S: Parse + Default,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > FromIterator<T> for Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut rep = Rep::new();
+        rep.extend(iter);
+        rep
+    }
+}
+
+impl<
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > FromIterator<Pair<T, S>> for Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    fn from_iter<I: IntoIterator<Item = Pair<T, S>>>(iter: I) -> Self {
+        let mut rep = Rep::new();
+        rep.extend(iter);
+        rep
+    }
+}
+
+impl<
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > IntoIterator for Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<
+        'a,
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > IntoIterator for &'a Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<
+        'a,
+        T: Parse,
+        S: Parse,
+        const COMPACT: bool,
+        P: TrailingPolicy,
+        const MIN: usize,
+        const MAX: usize,
+    > IntoIterator for &'a mut Rep<T, S, COMPACT, P, MIN, MAX>
+{
+    type Item = &'a mut T;
+    type IntoIter = core::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
     }
 }
 
@@ -109,3 +574,167 @@ fn test_parse_rep_path_compact() {
     assert_eq!(rep.items.len(), 2);
     assert_eq!(rep.seps.len(), 2);
 }
+
+#[test]
+fn test_rep_round_trip() {
+    let tokens: TokenStream = "a, b, c".parse().unwrap();
+    let rep = parse::<Rep<Ident, punct::Comma>>(&tokens).unwrap();
+    assert_eq!(rep.span().source_text(), Some("a, b, c"));
+    let round_trip = rep.to_token_stream();
+    assert_eq!(round_trip.len(), 5);
+    assert_eq!(parse::<Rep<Ident, punct::Comma>>(round_trip).unwrap(), rep);
+    // trailing separators are preserved
+    let tokens: TokenStream = "a, b,".parse().unwrap();
+    let rep = parse::<Rep<Ident, punct::Comma>>(&tokens).unwrap();
+    assert_eq!(rep.separators().len(), 2);
+    let round_trip = rep.to_token_stream();
+    assert_eq!(round_trip.len(), 4);
+    assert_eq!(parse::<Rep<Ident, punct::Comma>>(round_trip).unwrap(), rep);
+    // items without separators
+    let tokens: TokenStream = "a b c".parse().unwrap();
+    let rep = parse::<Rep<Ident>>(&tokens).unwrap();
+    assert_eq!(rep.to_token_stream().len(), 3);
+    assert_eq!(parse::<Rep<Ident>>(rep.to_token_stream()).unwrap(), rep);
+    let (rep, remaining) = parse_compact::<Rep<Ident, punct::Comma, true>>(&tokens).unwrap();
+    assert_eq!(remaining.len(), 2);
+    let round_trip = rep.to_token_stream();
+    assert_eq!(
+        parse::<Rep<Ident, punct::Comma, true>>(round_trip).unwrap(),
+        rep
+    );
+    let empty = parse::<Rep<Ident, punct::Comma>>(TokenStream::new()).unwrap();
+    assert!(empty.to_token_stream().is_empty());
+    assert!(empty.span().is_call_site());
+}
+
+#[test]
+fn test_rep_trailing_policies() {
+    type Forbidden = Rep<Ident, punct::Comma, false, trailing::Forbidden>;
+    type Required = Rep<Ident, punct::Semi, false, trailing::Required>;
+    let tokens: TokenStream = "a, b".parse().unwrap();
+    assert_eq!(parse::<Forbidden>(&tokens).unwrap().len(), 2);
+    let tokens: TokenStream = "a, b,".parse().unwrap();
+    let err = parse::<Forbidden>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "trailing `,` not allowed here");
+    assert_eq!(err.messages[0].span.source_text(), Some(","));
+    let tokens: TokenStream = "a; b;".parse().unwrap();
+    let rep = parse::<Required>(&tokens).unwrap();
+    assert!(rep.trailing_separator());
+    let tokens: TokenStream = "a; b".parse().unwrap();
+    let err = parse::<Required>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `;`, found end of tokens");
+    // in compact mode, a separator is trailing if no item follows it
+    let tokens: TokenStream = "a, b, 5".parse().unwrap();
+    let err =
+        parse_compact::<Rep<Ident, punct::Comma, true, trailing::Forbidden>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "trailing `,` not allowed here");
+    let tokens: TokenStream = "a b 5".parse().unwrap();
+    let (rep, remaining) =
+        parse_compact::<Rep<Ident, NoOp, true, trailing::Forbidden>>(&tokens).unwrap();
+    assert_eq!(rep.len(), 2);
+    assert_eq!(remaining.len(), 1);
+}
+
+#[test]
+fn test_rep_editing() {
+    let mut rep: Rep<Ident, punct::Comma> = ["a", "b"].into_iter().map(Ident::new).collect();
+    assert_eq!(rep.len(), 2);
+    assert_eq!(rep.separators().len(), 1);
+    assert!(!rep.trailing_separator());
+    rep.push(Ident::new("d"));
+    rep.insert(2, Ident::new("c"));
+    let names: Vec<&str> = rep.iter().map(|ident| ident.ident.as_str()).collect();
+    assert_eq!(names, ["a", "b", "c", "d"]);
+    assert_eq!(rep.separators().len(), 3);
+    rep.push_punct(punct::Comma::default());
+    assert!(rep.trailing_separator());
+    assert!(matches!(rep.pop(), Some(Pair::Punctuated(ident, _)) if ident == "d"));
+    // the separator between `c` and `d` now trails `c`
+    assert!(matches!(rep.pop(), Some(Pair::Punctuated(ident, _)) if ident == "c"));
+    assert!(rep.trailing_separator());
+    rep.push_value(Ident::new("e"));
+    for ident in rep.iter_mut() {
+        *ident = Ident::new(ident.ident.as_str().to_uppercase());
+    }
+    let pairs: Vec<Pair<Ident, punct::Comma>> = rep.clone().into_pairs().collect();
+    assert_eq!(pairs.len(), 3);
+    assert!(pairs[2].separator().is_none());
+    assert_eq!(*pairs[0].value(), "A");
+    let mut rebuilt: Rep<Ident, punct::Comma> = pairs.into_iter().collect();
+    assert_eq!(rebuilt, rep);
+    rebuilt.extend([Ident::new("F")]);
+    assert_eq!(rebuilt.to_token_stream().len(), 7);
+    assert_eq!(*rebuilt.last().unwrap(), "F");
+    rebuilt.clear();
+    assert!(rebuilt.is_empty());
+    assert!(rebuilt.pop().is_none());
+}
+
+#[test]
+#[should_panic]
+fn test_rep_push_value_without_separator() {
+    let mut rep: Rep<Ident, punct::Comma> = Rep::new();
+    rep.push_value(Ident::new("a"));
+    rep.push_value(Ident::new("b"));
+}
+
+#[test]
+fn test_rep_bounds() {
+    let tokens: TokenStream = "".parse().unwrap();
+    let err = parse::<Rep1<Ident, punct::Comma>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected at least 1 ident");
+    let tokens: TokenStream = "a, b".parse().unwrap();
+    assert_eq!(
+        parse::<Rep1<Ident, punct::Comma>>(&tokens).unwrap().len(),
+        2
+    );
+    type Pair = Rep<Ident, punct::Comma, false, trailing::Optional, 2, 2>;
+    let tokens: TokenStream = "a".parse().unwrap();
+    let err = parse::<Pair>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected at least 2 ident");
+    assert_eq!(err.messages[0].span.source_text(), Some("a"));
+    let tokens: TokenStream = "a, b, c".parse().unwrap();
+    let err = parse::<Pair>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected at most 2 ident");
+    assert_eq!(err.messages[0].span.source_text(), Some("a, b, c"));
+    let tokens: TokenStream = "a; b;".parse().unwrap();
+    let err = parse::<Rep<Ident, punct::Semi, false, trailing::Optional, 3>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected at least 3 ident");
+    assert_eq!(err.messages[0].span.source_text(), Some("a; b;"));
+    let tokens: TokenStream = ", ,".parse().unwrap();
+    let err =
+        parse::<Rep<punct::Comma, NoOp, false, trailing::Optional, 0, 1>>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected at most 1 `,`");
+    // compact repetitions stop once they are full
+    let tokens: TokenStream = "a, b, c".parse().unwrap();
+    let (rep, remaining) =
+        parse_compact::<Rep<Ident, punct::Comma, true, trailing::Forbidden, 1, 2>>(&tokens)
+            .unwrap();
+    assert_eq!(rep.len(), 2);
+    assert!(!rep.trailing_separator());
+    assert_eq!(remaining.len(), 2);
+}
+
+#[test]
+fn test_rep_cached_span() {
+    let tokens: TokenStream = "a, b, c".parse().unwrap();
+    let spans: Vec<Span> = tokens.iter().map(|token| token.span()).collect();
+    let mut rep = parse::<Rep<Ident, punct::Comma>>(&tokens).unwrap();
+    assert_eq!(rep.span(), rep.join_spans());
+    rep.pop();
+    assert_eq!(rep.span().source_text(), Some("a, b,"));
+    rep.push_value(Ident::new_spanned(spans[4], "c"));
+    assert_eq!(rep.span().source_text(), Some("a, b, c"));
+    // items modified in place are joined again
+    for ident in rep.iter_mut() {
+        ident.span = spans[0];
+    }
+    assert_eq!(rep.span().source_text(), Some("a, b,"));
+    rep.clear();
+    assert!(rep.span().is_call_site());
+    // the cache doesn't affect comparisons
+    let parsed = parse::<Rep<Ident, punct::Comma>>(&tokens).unwrap();
+    let mut stale = parsed.clone();
+    stale.iter_mut().for_each(drop);
+    assert_eq!(parsed, stale);
+}
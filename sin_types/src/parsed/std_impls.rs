@@ -0,0 +1,207 @@
+//! [`Parse`], [`ToTokens`] and [`Spanned`] for [`Option`], [`Box`], [`Vec`] and tuples, so
+//! that grammars can be composed without defining a new type for every sequence.
+
+use super::*;
+
+impl<T: ToTokens> ToTokens for Option<T> {
+    /// Emits the tokens of the inner value, or nothing if there is none.
+    fn to_token_stream(&self) -> TokenStream {
+        self.as_ref()
+            .map_or_else(TokenStream::new, ToTokens::to_token_stream)
+    }
+}
+
+impl<T: Spanned> Spanned for Option<T> {
+    /// Returns the [`Span`] of the inner value, or [`Span::call_site()`] if there is none.
+    fn span(&self) -> Span {
+        self.as_ref().map_or_else(Span::call_site, Spanned::span)
+    }
+}
+
+impl<T: Parse> Parse for Option<T> {
+    /// Parses a `T` if one can be parsed from the input, otherwise consumes nothing and
+    /// returns `None`. See [`Parser::try_parse`].
+    fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
+        input.try_parse()
+    }
+
+    fn description() -> InStr {
+        T::description()
+    }
+}
+
+impl<T: ToTokens> ToTokens for Box<T> {
+    fn to_token_stream(&self) -> TokenStream {
+        self.as_ref().to_token_stream()
+    }
+}
+
+impl<T: Spanned + ?Sized> Spanned for Box<T> {
+    fn span(&self) -> Span {
+        self.as_ref().span()
+    }
+}
+
+impl<T: Parse> Parse for Box<T> {
+    fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
+        Ok(Box::new(input.parse()?))
+    }
+
+    fn description() -> InStr {
+        T::description()
+    }
+}
+
+impl<T: ToTokens> ToTokens for Vec<T> {
+    fn to_token_stream(&self) -> TokenStream {
+        let mut tokens = TokenStream::new();
+        for item in self {
+            tokens.extend(item.to_token_stream());
+        }
+        tokens
+    }
+}
+
+impl<T: Spanned> Spanned for Vec<T> {
+    /// Returns the joined [`Span`] of all of the items, or [`Span::call_site()`] if there are
+    /// none.
+    fn span(&self) -> Span {
+        let spans: Vec<Span> = self.iter().map(Spanned::span).collect();
+        __private::join_spans(&spans)
+    }
+}
+
+impl<T: Parse> Parse for Vec<T> {
+    /// Greedily parses as many `T`s as possible, stopping at the first one that fails to parse
+    /// (or that would consume nothing). Use [`Rep`] for separated or bounded repetitions.
+    fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
+        let mut items = Vec::new();
+        while input.peek().is_some() {
+            let mut fork = input.clone();
+            let Some(item) = fork.try_parse::<T>()? else {
+                break;
+            };
+            if fork.cursor() == input.cursor() {
+                break;
+            }
+            *input = fork;
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    fn description() -> InStr {
+        T::description()
+    }
+}
+
+macro_rules! impl_parse_tuple {
+    ($first:ident $($ty:ident)*; $($idx:tt)+) => {
+        impl<$first: ToTokens, $($ty: ToTokens),*> ToTokens for ($first, $($ty,)*) {
+            fn to_token_stream(&self) -> TokenStream {
+                let mut tokens = TokenStream::new();
+                $(tokens.extend(self.$idx.to_token_stream());)+
+                tokens
+            }
+        }
+
+        impl<$first: Spanned, $($ty: Spanned),*> Spanned for ($first, $($ty,)*) {
+            fn span(&self) -> Span {
+                __private::join_spans(&[$(self.$idx.span()),+])
+            }
+        }
+
+        impl<$first: Parse, $($ty: Parse),*> Parse for ($first, $($ty,)*) {
+            /// Parses each element in order.
+            fn parse<'a, I: Default + Clone>(input: &mut Parser<'a, I>) -> ParseResult<Self> {
+                Ok((input.parse::<$first>()?, $(input.parse::<$ty>()?,)*))
+            }
+
+            /// Describes the tuple by its first element, which is what is expected first.
+            fn description() -> InStr {
+                $first::description()
+            }
+        }
+    };
+}
+
+impl_parse_tuple!(A; 0);
+impl_parse_tuple!(A B; 0 1);
+impl_parse_tuple!(A B C; 0 1 2);
+impl_parse_tuple!(A B C D; 0 1 2 3);
+impl_parse_tuple!(A B C D E; 0 1 2 3 4);
+impl_parse_tuple!(A B C D E F; 0 1 2 3 4 5);
+impl_parse_tuple!(A B C D E F G; 0 1 2 3 4 5 6);
+impl_parse_tuple!(A B C D E F G H; 0 1 2 3 4 5 6 7);
+impl_parse_tuple!(A B C D E F G H J; 0 1 2 3 4 5 6 7 8);
+impl_parse_tuple!(A B C D E F G H J K; 0 1 2 3 4 5 6 7 8 9);
+impl_parse_tuple!(A B C D E F G H J K L; 0 1 2 3 4 5 6 7 8 9 10);
+impl_parse_tuple!(A B C D E F G H J K L M; 0 1 2 3 4 5 6 7 8 9 10 11);
+
+#[test]
+fn test_parse_option_and_box() {
+    let tokens: TokenStream = "a: b".parse().unwrap();
+    let mut input = tokens.to_parser();
+    let colon = input.parse::<Option<punct::Colon>>().unwrap();
+    assert!(colon.is_none());
+    assert_eq!(input.cursor(), 0);
+    assert!(colon.to_token_stream().is_empty());
+    assert!(colon.span().is_call_site());
+    let ident = input.parse::<Box<Ident>>().unwrap();
+    assert_eq!(*ident, "a");
+    assert_eq!(ident.span().source_text(), Some("a"));
+    let colon = input.parse::<Option<punct::Colon>>().unwrap();
+    assert_eq!(colon.span().source_text(), Some(":"));
+    assert_eq!(colon.to_token_stream().len(), 1);
+}
+
+#[test]
+fn test_parse_vec() {
+    let tokens: TokenStream = "a b c, d".parse().unwrap();
+    let (idents, remaining) = parse_compact::<Vec<Ident>>(&tokens).unwrap();
+    assert_eq!(idents.len(), 3);
+    assert_eq!(remaining.len(), 2);
+    assert_eq!(idents.span().source_text(), Some("a b c"));
+    assert_eq!(idents.to_token_stream().len(), 3);
+    assert_eq!(
+        parse::<Vec<Ident>>(idents.to_token_stream()).unwrap(),
+        idents
+    );
+    // items that consume nothing don't loop forever
+    let nothing = parse::<Vec<Option<Ident>>>(TokenStream::new()).unwrap();
+    assert!(nothing.is_empty());
+    let (options, _) = parse_compact::<Vec<Option<punct::Comma>>>(&tokens).unwrap();
+    assert!(options.is_empty());
+}
+
+#[test]
+fn test_parse_tuples() {
+    let tokens: TokenStream = "a: b".parse().unwrap();
+    let parsed = parse::<(Ident, punct::Colon, Option<Ident>)>(&tokens).unwrap();
+    assert_eq!(parsed.0, "a");
+    assert_eq!(parsed.2.unwrap(), "b");
+    assert_eq!(parsed.span().source_text(), Some("a: b"));
+    assert_eq!(parsed.to_token_stream().len(), 3);
+    let tokens: TokenStream = "a:".parse().unwrap();
+    let parsed = parse::<(Ident, punct::Colon, Option<Ident>)>(&tokens).unwrap();
+    assert!(parsed.2.is_none());
+    let err = parse::<(Ident, punct::Colon)>(&"a b".parse::<TokenStream>().unwrap()).unwrap_err();
+    assert_eq!(err.messages[0], "expected `:`, found `b`");
+    assert_eq!(<(Ident, punct::Colon)>::description(), "ident");
+    let tokens: TokenStream = "a b c d e f g h i j k l".parse().unwrap();
+    type Twelve = (
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+        Ident,
+    );
+    assert_eq!(parse::<Twelve>(&tokens).unwrap().11, "l");
+}
@@ -0,0 +1,43 @@
+//! Type-level policies controlling whether the last item of a [`Rep`](crate::Rep) may be
+//! followed by a separator.
+
+/// A policy for trailing separators, used as the `P` parameter of [`Rep`](crate::Rep).
+///
+/// Policies are enforced while parsing. Editing a [`Rep`](crate::Rep), i.e. via
+/// [`Rep::push_punct`](crate::Rep::push_punct), is not restricted by its policy.
+pub trait TrailingPolicy:
+    Copy + Clone + PartialEq + Eq + PartialOrd + Ord + core::hash::Hash + core::fmt::Debug + Default
+{
+    /// Whether the last item may be followed by a separator.
+    const ALLOWED: bool;
+    /// Whether every item, including the last, must be followed by a separator.
+    const REQUIRED: bool;
+}
+
+/// The last item of a [`Rep`](crate::Rep) must not be followed by a separator, i.e. `a, b`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Forbidden;
+
+impl TrailingPolicy for Forbidden {
+    const ALLOWED: bool = false;
+    const REQUIRED: bool = false;
+}
+
+/// The last item of a [`Rep`](crate::Rep) may or may not be followed by a separator, i.e.
+/// `a, b` or `a, b,`. This is the default.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Optional;
+
+impl TrailingPolicy for Optional {
+    const ALLOWED: bool = true;
+    const REQUIRED: bool = false;
+}
+
+/// Every item of a [`Rep`](crate::Rep) must be followed by a separator, i.e. `a; b;`.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Required;
+
+impl TrailingPolicy for Required {
+    const ALLOWED: bool = true;
+    const REQUIRED: bool = true;
+}
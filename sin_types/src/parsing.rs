@@ -2,6 +2,8 @@ use crate::{
     token_stream::{Peekable, TSIterator},
     *,
 };
+use core::{any::Any, any::TypeId, cell::RefCell, hash::Hash};
+use std::{collections::HashMap, hash::DefaultHasher, rc::Rc};
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ParseError {
@@ -9,33 +11,116 @@ pub struct ParseError {
 }
 
 impl core::fmt::Display for ParseError {
+    /// Renders every message like rustc would, see [`Renderer`].
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        for message in &self.messages {
-            message.fmt(f)?;
-        }
-        Ok(())
+        f.write_str(&Renderer::new().render(self))
     }
 }
 
 impl core::fmt::Debug for ParseError {
+    /// Renders every message like [`Display`](core::fmt::Display) does, so that unwrapping a
+    /// [`ParseResult`] shows where each error is.
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        for message in &self.messages {
-            message.fmt(f)?;
-        }
-        Ok(())
+        f.write_str(&Renderer::new().render(self))
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ErrorMessage {
     pub span: Span,
     pub message: InStr,
+    /// The [`TokenPattern`]s that were expected, if this is an "expected ..., found ..." error.
+    pub expected: Vec<TokenPattern>,
+    /// The [`Token`] that was found instead of the `expected` patterns, if any.
+    pub found: Option<Token>,
+    /// Secondary [`Span`]s relevant to the error, i.e. "first defined here".
+    pub labels: Vec<Label>,
+    /// `note: ...` lines giving more context about the error.
+    pub notes: Vec<InStr>,
+    /// `help: ...` lines describing how to fix the error.
+    pub help: Vec<InStr>,
+    /// Replacements that would fix the error, i.e. "replace `=>` with `->`".
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// A secondary [`Span`] of an [`ErrorMessage`], along with what it points out.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Label {
+    pub span: Span,
+    pub message: InStr,
+}
+
+/// A machine-applicable fix for an [`ErrorMessage`]: replacing the source text of `span` with
+/// `replacement`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Suggestion {
+    pub span: Span,
+    /// Describes the fix, i.e. "use `->` for return types".
+    pub message: InStr,
+    pub replacement: InStr,
+}
+
+impl ErrorMessage {
+    pub fn new(span: Span, message: impl Into<InStr>) -> Self {
+        ErrorMessage {
+            span,
+            message: message.into(),
+            expected: Vec::new(),
+            found: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Points out a secondary [`Span`], i.e. where a duplicate field was first defined.
+    pub fn label(mut self, span: Span, message: impl Into<InStr>) -> Self {
+        let message = message.into();
+        self.labels.push(Label { span, message });
+        self
+    }
+
+    /// Adds a `note: ...` line.
+    pub fn note(mut self, note: impl Into<InStr>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Adds a `help: ...` line.
+    pub fn help(mut self, help: impl Into<InStr>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    /// Suggests replacing the source text of `span` with `replacement` to fix this error.
+    pub fn suggestion(
+        mut self,
+        span: Span,
+        message: impl Into<InStr>,
+        replacement: impl Into<InStr>,
+    ) -> Self {
+        self.suggestions.push(Suggestion {
+            span,
+            message: message.into(),
+            replacement: replacement.into(),
+        });
+        self
+    }
+}
+
+impl From<ErrorMessage> for ParseError {
+    fn from(message: ErrorMessage) -> Self {
+        ParseError {
+            messages: vec![message],
+        }
+    }
 }
 
 impl core::fmt::Display for ErrorMessage {
+    /// Renders the message like rustc would, see [`Renderer`].
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        f.write_fmt(format_args!("error: {}", self.message))?;
-        Ok(())
+        f.write_str(&Renderer::new().render_message(self))
     }
 }
 
@@ -52,7 +137,7 @@ impl Spanned for ErrorMessage {
     }
 }
 
-impl<'a> PartialEq<&'a str> for ErrorMessage {
+impl PartialEq<&str> for ErrorMessage {
     fn eq(&self, other: &&str) -> bool {
         self.message == *other
     }
@@ -71,74 +156,861 @@ impl ParseError {
         }
     }
 
+    /// Appends a custom error `message` pointing at the specified [`Span`].
+    pub fn error(&self, span: Span, message: impl Into<InStr>) -> Self {
+        let mut this = self.clone();
+        this.messages.push(ErrorMessage::new(span, message));
+        this
+    }
+
+    /// Appends a finished [`ErrorMessage`], i.e. one built with its own builder methods.
+    pub fn message(&self, message: ErrorMessage) -> Self {
+        let mut this = self.clone();
+        this.messages.push(message);
+        this
+    }
+
     pub fn expected_token(&self, expected: TokenPattern, found: Option<Token>, span: Span) -> Self {
         let mut this = self.clone();
-        let message = match found {
-            Some(found) => format!("expected {expected}, found `{found}`").into(),
-            None => format!("expected {expected}, found end of tokens").into(),
-        };
-        this.messages.push(ErrorMessage { span, message });
+        this.messages.push(ErrorMessage {
+            expected: vec![expected],
+            found,
+            ..ErrorMessage::new(span, expected_message(&[expected], found))
+        });
+        this
+    }
+
+    /// Returns a copy of this [`ParseError`] with `f` applied to its last message, i.e. the one
+    /// added by the preceding call to [`ParseError::error`] or [`ParseError::expected_token`].
+    ///
+    /// Panics if there is no last message. Build an [`ErrorMessage`] first and append it via
+    /// [`ParseError::message`] to rule this out.
+    fn with_last(&self, f: impl FnOnce(ErrorMessage) -> ErrorMessage) -> Self {
+        let mut this = self.clone();
+        let message = this.messages.pop().expect(
+            "ParseError builder methods apply to the last message, but there are no messages",
+        );
+        this.messages.push(f(message));
         this
     }
+
+    /// Points out a secondary [`Span`] in the last message, see [`ErrorMessage::label`].
+    pub fn label(&self, span: Span, message: impl Into<InStr>) -> Self {
+        self.with_last(|last| last.label(span, message))
+    }
+
+    /// Adds a `note: ...` line to the last message.
+    pub fn note(&self, note: impl Into<InStr>) -> Self {
+        self.with_last(|last| last.note(note))
+    }
+
+    /// Adds a `help: ...` line to the last message.
+    pub fn help(&self, help: impl Into<InStr>) -> Self {
+        self.with_last(|last| last.help(help))
+    }
+
+    /// Suggests replacing the source text of `span` with `replacement` to fix the last message.
+    pub fn suggestion(
+        &self,
+        span: Span,
+        message: impl Into<InStr>,
+        replacement: impl Into<InStr>,
+    ) -> Self {
+        self.with_last(|last| last.suggestion(span, message, replacement))
+    }
+
+    /// Merges the messages of `other` into this [`ParseError`].
+    ///
+    /// "expected ..., found ..." messages pointing at the same [`Span`] and found [`Token`]
+    /// are combined into a single message listing all of the expected patterns, i.e.
+    /// "expected `fn`, `struct` or ident, found `,`", along with the labels, notes, help and
+    /// suggestions of all of them.
+    pub fn merge(&self, other: &ParseError) -> Self {
+        let mut this = self.clone();
+        for message in &other.messages {
+            let existing = this.messages.iter_mut().find(|existing| {
+                !existing.expected.is_empty()
+                    && !message.expected.is_empty()
+                    && existing.span == message.span
+                    && existing.found == message.found
+            });
+            let Some(existing) = existing else {
+                this.messages.push(message.clone());
+                continue;
+            };
+            extend_unique(&mut existing.expected, &message.expected);
+            extend_unique(&mut existing.labels, &message.labels);
+            extend_unique(&mut existing.notes, &message.notes);
+            extend_unique(&mut existing.help, &message.help);
+            extend_unique(&mut existing.suggestions, &message.suggestions);
+            existing.message = expected_message(&existing.expected, existing.found);
+        }
+        this
+    }
+
+    /// Turns every message into a `::core::compile_error!(...)` invocation spanned at the
+    /// message's [`Span`], so that the compiler reports each error at the input tokens that
+    /// caused it. Returning these tokens from a proc macro fails compilation with all of the
+    /// messages.
+    ///
+    /// Since `compile_error!` only takes a message, notes and help are appended to it, while
+    /// labels and suggestions are reported as separate errors at their own [`Span`]s.
+    pub fn to_compile_error(&self) -> TokenStream {
+        let mut tokens = TokenStream::new();
+        for message in &self.messages {
+            let mut text = message.message.to_string();
+            for note in &message.notes {
+                text.push_str(&format!("\n= note: {note}"));
+            }
+            for help in &message.help {
+                text.push_str(&format!("\n= help: {help}"));
+            }
+            tokens.extend(compile_error(message.span, &text));
+            for label in &message.labels {
+                tokens.extend(compile_error(label.span, &label.message));
+            }
+            for suggestion in &message.suggestions {
+                let text = format!("help: {}", suggestion_message(suggestion));
+                tokens.extend(compile_error(suggestion.span, &text));
+            }
+        }
+        tokens
+    }
+
+    /// Like [`ParseError::to_compile_error`], but returns a [`proc_macro::TokenStream`] to be
+    /// returned from a proc macro directly. Must only be called from within a proc macro.
+    pub fn to_compile_error_proc_macro(&self) -> proc_macro::TokenStream {
+        self.to_compile_error().into()
+    }
+}
+
+/// Appends the `items` that `vec` doesn't contain yet.
+fn extend_unique<T: PartialEq + Clone>(vec: &mut Vec<T>, items: &[T]) {
+    for item in items {
+        if !vec.contains(item) {
+            vec.push(item.clone());
+        }
+    }
+}
+
+/// Generates a `::core::compile_error!(...)` invocation reporting `message` at `span`.
+fn compile_error(span: Span, message: &str) -> TokenStream {
+    let mut tokens: TokenStream = format!("::core::compile_error!({message:?});")
+        .parse()
+        .expect("a string literal always lexes");
+    tokens.respan(span);
+    tokens
+}
+
+pub(crate) fn suggestion_message(suggestion: &Suggestion) -> String {
+    format!("{}: `{}`", suggestion.message, suggestion.replacement)
+}
+
+fn expected_message(expected: &[TokenPattern], found: Option<Token>) -> InStr {
+    let expected: Vec<String> = expected.iter().map(|p| p.to_string()).collect();
+    let expected = or_list(&expected);
+    match found {
+        Some(found) => format!("expected {expected}, found `{found}`").into(),
+        None => format!("expected {expected}, found end of tokens").into(),
+    }
+}
+
+/// Formats `items` as an English list of alternatives, i.e. "A, B or C".
+pub(crate) fn or_list(items: &[String]) -> String {
+    match items {
+        [] => String::from("nothing"),
+        [item] => item.clone(),
+        [items @ .., last] => format!("{} or {last}", items.join(", ")),
+    }
+}
+
+impl Default for ParseError {
+    fn default() -> Self {
+        ParseError::new()
+    }
 }
 
 pub type ParseResult<T> = Result<T, ParseError>;
 
+/// Parses an ordered choice between several alternatives, such as the variants of an enum
+/// deriving [`Parse`].
+///
+/// Alternatives are tried in order and the first one that matches is committed to. If none of
+/// them match, [`Alternatives::into_error`] reports the failures that made the most progress,
+/// merged via [`ParseError::merge`]. If an alternative fails after calling [`Parser::commit`],
+/// the remaining alternatives are skipped and only its error is reported.
+#[derive(Clone, Debug, Default)]
+pub struct Alternatives {
+    progress: usize,
+    error: Option<ParseError>,
+    committed: bool,
+}
+
+impl Alternatives {
+    pub fn new() -> Self {
+        Alternatives::default()
+    }
+
+    /// Attempts to parse an alternative by calling `parse` on a fork of `input`, advancing
+    /// `input` only if it succeeds. Does nothing if a previous alternative failed after
+    /// committing.
+    pub fn attempt<'a, T: Default + Clone, R>(
+        &mut self,
+        input: &mut Parser<'a, T>,
+        parse: impl FnOnce(&mut Parser<'a, T>) -> ParseResult<R>,
+    ) -> Option<R> {
+        if self.committed {
+            return None;
+        }
+        let mut fork = input.clone();
+        fork.committed = false;
+        match parse(&mut fork) {
+            Ok(value) => {
+                // committing only affects the choice between these alternatives
+                fork.committed = input.committed;
+                *input = fork;
+                Some(value)
+            }
+            Err(error) if fork.committed => {
+                self.committed = true;
+                self.error = Some(error);
+                None
+            }
+            Err(error) => {
+                self.record(fork.cursor(), error);
+                None
+            }
+        }
+    }
+
+    /// Returns `true` if the next token of `input` matches `pattern`, in which case the caller
+    /// should commit to the corresponding alternative. Otherwise `pattern` is recorded as
+    /// expected.
+    pub fn peek<'a, T: Default + Clone>(
+        &mut self,
+        input: &Parser<'a, T>,
+        pattern: TokenPattern,
+    ) -> bool {
+        if self.committed {
+            return false;
+        }
+        let found = input.peek();
+        if let Some(tt) = &found {
+            if Token::from(tt.clone()).matches(pattern) {
+                return true;
+            }
+        }
+        // a mismatched token counts as consumed, just like when parsing it fails
+        let progress = input.cursor() + found.is_some() as usize;
+        let span = found.as_ref().map(|tt| tt.span()).unwrap_or(input.span());
+        let error = ParseError::new().expected_token(pattern, found.map(Token::from), span);
+        self.record(progress, error);
+        false
+    }
+
+    fn record(&mut self, progress: usize, error: ParseError) {
+        match &self.error {
+            Some(_) if progress < self.progress => (),
+            Some(existing) if progress == self.progress => {
+                self.error = Some(existing.merge(&error));
+            }
+            _ => {
+                self.progress = progress;
+                self.error = Some(error);
+            }
+        }
+    }
+
+    /// Returns the merged error of the alternatives that made the most progress.
+    pub fn into_error<'a, T: Default + Clone>(self, input: &Parser<'a, T>) -> ParseError {
+        self.error
+            .unwrap_or_else(|| ParseError::new().error(input.span(), "no alternatives to parse"))
+    }
+}
+
+/// Probes the next tokens of a [`Parser`] without consuming them, remembering everything that
+/// was tested so that [`Lookahead::error`] can report all of it, i.e.
+/// "expected `fn`, `struct` or `enum`, found `impl`".
+///
+/// Created by [`Parser::lookahead`].
+#[derive(Clone)]
+pub struct Lookahead<'p, 'a, T: Default + Clone> {
+    input: &'p Parser<'a, T>,
+    alternatives: Alternatives,
+}
+
+impl<'p, 'a, T: Default + Clone> Lookahead<'p, 'a, T> {
+    /// Returns `true` if the next [`TokenTree`] matches `pattern`, otherwise records `pattern`
+    /// as expected.
+    pub fn peek(&mut self, pattern: TokenPattern) -> bool {
+        self.alternatives.peek(self.input, pattern)
+    }
+
+    /// Returns `true` if a `P` can be parsed from the input, otherwise records why it couldn't.
+    pub fn peek_parse<P: Parse>(&mut self) -> bool {
+        let mut fork = self.input.clone();
+        self.alternatives.attempt(&mut fork, P::parse).is_some()
+    }
+
+    /// Returns an error listing everything that was probed, merged at the furthest position
+    /// that was reached.
+    pub fn error(self) -> ParseError {
+        self.alternatives.into_error(self.input)
+    }
+}
+
+/// The cursor, [`TypeId`] and hashed starting state of a memoized rule.
+type MemoKey = (usize, TypeId, u64);
+
+/// The packrat memoization table of a [`Parser`], shared by all of its forks.
+struct Memo<T> {
+    limit: Option<usize>,
+    /// Hashes and compares states, which [`Parser::memoize`] requires to be `Hash + Eq`.
+    hash_state: fn(&T) -> u64,
+    eq_state: fn(&T, &T) -> bool,
+    entries: HashMap<MemoKey, MemoEntry<T>>,
+    /// The rules currently being parsed, innermost last.
+    stack: Vec<MemoKey>,
+}
+
+/// The `ParseResult` of a memoized rule, along with the cursor and state it left the
+/// [`Parser`] in.
+struct MemoEntry<T> {
+    /// The state the rule was parsed with, to tell apart states with the same hash.
+    start: T,
+    result: Box<dyn Any>,
+    cursor: usize,
+    state: T,
+    /// Whether the rule is still being parsed, in which case this is a (growing) seed.
+    in_progress: bool,
+    /// Whether the rule tried to parse itself while in progress, i.e. it is left-recursive.
+    recursed: bool,
+    /// Whether the rule called [`Parser::commit`].
+    committed: bool,
+    /// The errors the rule recovered from, see [`Parser::parse_recover`].
+    errors: Vec<ErrorMessage>,
+    /// Whether the rule depends on the seed of a left-recursive rule that is still growing, in
+    /// which case its result must not be cached.
+    involved: bool,
+}
+
+impl<T: Clone> Memo<T> {
+    fn new(limit: Option<usize>) -> Rc<RefCell<Self>>
+    where
+        T: Hash + Eq,
+    {
+        Rc::new(RefCell::new(Memo {
+            limit,
+            hash_state: |state| {
+                let mut hasher = DefaultHasher::new();
+                state.hash(&mut hasher);
+                core::hash::Hasher::finish(&hasher)
+            },
+            eq_state: T::eq,
+            entries: HashMap::new(),
+            stack: Vec::new(),
+        }))
+    }
+
+    /// Returns an empty table with the same limit, i.e. for a nested [`Parser`].
+    fn empty(&self) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Memo {
+            limit: self.limit,
+            hash_state: self.hash_state,
+            eq_state: self.eq_state,
+            entries: HashMap::new(),
+            stack: Vec::new(),
+        }))
+    }
+
+    /// Plants a failing seed for the rule at `key` and marks it as in progress.
+    fn enter(&mut self, key: MemoKey, state: T) {
+        if self.limit.is_some_and(|limit| self.entries.len() >= limit) {
+            // seeds must survive, otherwise left-recursive rules would recurse forever
+            self.entries.retain(|_, entry| entry.in_progress);
+        }
+        let entry = MemoEntry {
+            start: state.clone(),
+            result: Box::new(()),
+            cursor: key.0,
+            state,
+            in_progress: true,
+            recursed: false,
+            committed: false,
+            errors: Vec::new(),
+            involved: false,
+        };
+        self.entries.insert(key, entry);
+        self.stack.push(key);
+    }
+
+    /// Records that the seed at `key` was used, so its rule is left-recursive and every rule
+    /// between it and the innermost one depends on its seed.
+    fn recurse(&mut self, key: MemoKey) {
+        let Some(index) = self.stack.iter().rposition(|k| *k == key) else {
+            return;
+        };
+        for k in &self.stack[index + 1..] {
+            if let Some(entry) = self.entries.get_mut(k) {
+                entry.involved = true;
+            }
+        }
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.recursed = true;
+        }
+    }
+
+    fn update<P: Parse + 'static>(
+        &mut self,
+        key: MemoKey,
+        result: ParseResult<P>,
+        cursor: usize,
+        state: T,
+        committed: bool,
+        errors: &[ErrorMessage],
+    ) {
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.result = Box::new(result);
+            entry.cursor = cursor;
+            entry.state = state;
+            entry.committed = committed;
+            entry.errors = errors.to_vec();
+        }
+    }
+
+    /// Finishes parsing the rule at `key`, caching its final result unless it is involved in
+    /// growing a seed.
+    fn exit(&mut self, key: MemoKey) {
+        self.stack.pop();
+        match self.entries.get_mut(&key) {
+            Some(entry) if entry.involved => {
+                self.entries.remove(&key);
+            }
+            Some(entry) => entry.in_progress = false,
+            None => (),
+        }
+    }
+}
+
 #[derive(Clone)]
-pub struct Parser<'a, T: Default + Clone = ()>(TSIterator<'a, T>, Span);
+pub struct Parser<'a, T: Default + Clone = ()> {
+    /// The remaining input, along with the state.
+    iter: TSIterator<'a, T>,
+    /// Where the end of the input is reported.
+    span: Span,
+    /// The memoization table shared with forks, see [`Parser::memoize`].
+    memo: Option<Rc<RefCell<Memo<T>>>>,
+    /// Whether the current alternative has been committed to, see [`Parser::commit`].
+    committed: bool,
+    /// The errors recovered from so far, see [`Parser::parse_recover`].
+    errors: ParseError,
+}
 
 impl<'a, T: Default + Clone> Iterator for Parser<'a, T> {
     type Item = TokenTree;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        self.iter.next()
     }
 }
 
 impl<'a, T: Default + Clone> Peekable<TokenTree> for Parser<'a, T> {
     fn peek_n(&self, n: isize) -> Option<TokenTree> {
-        self.0.peek_n(n)
+        self.iter.peek_n(n)
     }
 
     fn peek(&self) -> Option<TokenTree> {
-        self.0.peek()
+        self.iter.peek()
     }
 }
 
 impl<'a, T: Default + Clone> Parser<'a, T> {
     pub fn new(iter: TSIterator<'a, T>, span: Span) -> Self {
-        Parser(iter, span)
+        Parser {
+            iter,
+            span,
+            memo: None,
+            committed: false,
+            errors: ParseError::new(),
+        }
     }
 
     pub fn state_mut(&mut self) -> &mut T {
-        &mut self.0.state
+        &mut self.iter.state
     }
 
     pub fn state(&self) -> &T {
-        &self.0.state
+        &self.iter.state
     }
 
     pub fn span(&self) -> Span {
-        self.1
+        self.span
     }
 
     pub fn set_span(&mut self, span: Span) {
-        self.1 = span
+        self.span = span
+    }
+
+    /// Returns the index of the next [`TokenTree`] this [`Parser`] will consume.
+    pub fn cursor(&self) -> usize {
+        self.iter.cursor()
     }
 
     pub fn parse<P: Parse>(&mut self) -> ParseResult<P> {
-        P::parse(self)
+        P::parse_memoized(self)
+    }
+
+    /// Parses a `P` through the memoization table of this [`Parser`], if it has one (see
+    /// [`Parser::memoize`]), otherwise like [`Parse::parse`]. Types opt into being memoized by
+    /// calling this from [`Parse::parse_memoized`].
+    pub fn parse_memo<P: Parse + 'static>(&mut self) -> ParseResult<P> {
+        let Some(memo) = self.memo.clone() else {
+            return P::parse(self);
+        };
+        let start = self.cursor();
+        let state = self.state().clone();
+        let key = (start, TypeId::of::<P>(), (memo.borrow().hash_state)(&state));
+        let collided = {
+            let memo = memo.borrow();
+            let entry = memo.entries.get(&key);
+            entry.is_some_and(|entry| !(memo.eq_state)(&entry.start, &state))
+        };
+        if collided {
+            // another state with the same hash is cached for this rule at this position
+            return P::parse(self);
+        }
+        let cached = memo.borrow().entries.get(&key).map(|entry| {
+            let result = entry.result.downcast_ref::<ParseResult<P>>().cloned();
+            let (cursor, state, errors) = (entry.cursor, entry.state.clone(), entry.errors.clone());
+            (
+                result,
+                cursor,
+                state,
+                entry.committed,
+                errors,
+                entry.in_progress,
+            )
+        });
+        if let Some((result, cursor, state, committed, errors, in_progress)) = cached {
+            if in_progress {
+                memo.borrow_mut().recurse(key);
+            }
+            self.iter.set_cursor(cursor);
+            *self.state_mut() = state;
+            self.committed |= committed;
+            self.errors.messages.extend(errors);
+            // a seed that hasn't grown yet fails
+            return result.unwrap_or_else(|| Err(ParseError::new()));
+        }
+        // only cache whether the rule itself commits, not whether it was parsed while committed
+        let committed = core::mem::replace(&mut self.committed, false);
+        // likewise, only cache the errors the rule itself recovers from
+        let recovered = self.errors.messages.len();
+        memo.borrow_mut().enter(key, state.clone());
+        let mut result = P::parse(self);
+        let recursed = memo.borrow().entries.get(&key).is_some_and(|e| e.recursed);
+        if recursed {
+            // grow the seed by parsing again on top of the previous result, for as long as
+            // that consumes more tokens
+            while result.is_ok() {
+                let (end, end_state, end_committed) =
+                    (self.cursor(), self.state().clone(), self.committed);
+                let end_errors = self
+                    .errors
+                    .messages
+                    .split_off(self.recovered_since(recovered));
+                memo.borrow_mut().update(
+                    key,
+                    result.clone(),
+                    end,
+                    end_state.clone(),
+                    end_committed,
+                    &end_errors,
+                );
+                self.iter.set_cursor(start);
+                *self.state_mut() = state.clone();
+                self.committed = false;
+                let grown = P::parse(self);
+                if grown.is_err() || self.cursor() <= end {
+                    self.iter.set_cursor(end);
+                    *self.state_mut() = end_state;
+                    self.committed = end_committed;
+                    self.errors.messages.truncate(recovered);
+                    self.errors.messages.extend(end_errors);
+                    break;
+                }
+                result = grown;
+            }
+        }
+        let mut memo = memo.borrow_mut();
+        memo.update(
+            key,
+            result.clone(),
+            self.cursor(),
+            self.state().clone(),
+            self.committed,
+            &self.errors.messages[self.recovered_since(recovered)..],
+        );
+        memo.exit(key);
+        self.committed |= committed;
+        result
     }
 
     pub fn peek_parse<P: Parse>(&self) -> ParseResult<P> {
         let mut fork = self.clone();
-        P::parse(&mut fork)
+        fork.parse::<P>()
+    }
+
+    /// Parses a `P` on a fork of this [`Parser`], advancing it only if that succeeds. If it
+    /// fails, nothing is consumed and `None` is returned, unless `P` failed after calling
+    /// [`Parser::commit`], in which case its error is returned instead.
+    pub fn try_parse<P: Parse>(&mut self) -> ParseResult<Option<P>> {
+        let mut fork = self.clone();
+        fork.committed = false;
+        match fork.parse::<P>() {
+            Ok(value) => {
+                fork.committed = self.committed;
+                *self = fork;
+                Ok(Some(value))
+            }
+            Err(error) if fork.committed => Err(error),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Commits to the alternative currently being parsed: if it fails from now on, enclosing
+    /// choices between alternatives (such as derived enums, [`Either`], [`Option`] or a compact
+    /// [`Rep`]) report its error instead of trying the remaining alternatives.
+    ///
+    /// Only the innermost choice is affected. Parsing [`Cut`] calls this.
+    pub fn commit(&mut self) {
+        self.committed = true;
+    }
+
+    /// Returns `true` if [`Parser::commit`] was called within the current alternative.
+    pub fn is_committed(&self) -> bool {
+        self.committed
+    }
+
+    /// Enables packrat memoization for this [`Parser`] and every fork of it made from now on.
+    ///
+    /// Each time a rule is parsed at a position, its result is cached along with the position
+    /// and state it left the parser in, so parsing the same rule at the same position and with
+    /// the same state again, i.e. while trying the alternatives of an ordered choice, replays
+    /// the cached result instead. Only rules that opt in through [`Parse::parse_memoized`] are
+    /// cached, which `derive(Parse)` does for types without generic type or lifetime
+    /// parameters, since caching requires `'static` types.
+    ///
+    /// Memoization also makes directly left-recursive rules possible, such as
+    /// `Expr := Expr "." Ident | Atom`: while a rule is being parsed at a position, parsing it
+    /// again at that position fails, so the other alternatives provide a seed that is then
+    /// grown for as long as the left-recursive alternative consumes more tokens. Indirect left
+    /// recursion, through other rules, is not supported. [`parse`], [`Parse::parse_tokens`]
+    /// and the `#[sin::proc_macro]` entry points memoize, while parsers created by hand only do
+    /// once this is called.
+    ///
+    /// Nested parsers, i.e. for the content of a [`Group`], get their own table with the same
+    /// limit.
+    pub fn memoize(&mut self)
+    where
+        T: Hash + Eq,
+    {
+        self.memo = Some(Memo::new(None));
+    }
+
+    /// Like [`Parser::memoize`], but the table is cleared whenever it would exceed `limit`
+    /// entries, bounding its memory usage.
+    pub fn memoize_bounded(&mut self, limit: usize)
+    where
+        T: Hash + Eq,
+    {
+        self.memo = Some(Memo::new(Some(limit)));
+    }
+
+    /// Returns `true` if this [`Parser`] memoizes rules, see [`Parser::memoize`].
+    pub fn is_memoized(&self) -> bool {
+        self.memo.is_some()
+    }
+
+    /// Returns the number of rules cached by this [`Parser`] and its forks.
+    pub fn memo_len(&self) -> usize {
+        self.memo
+            .as_ref()
+            .map_or(0, |memo| memo.borrow().entries.len())
+    }
+
+    /// Clears the rules cached by this [`Parser`] and its forks, without disabling memoization.
+    pub fn clear_memo(&self) {
+        if let Some(memo) = &self.memo {
+            memo.borrow_mut().entries.clear();
+        }
+    }
+
+    /// Parses a `P`, recovering if it fails: the error is recorded (see [`Parser::errors`]) and
+    /// the tokens up to and including the next `S` are skipped, or up to the end of the input
+    /// if there is none. This allows reporting more than one error at once, i.e. for every
+    /// malformed statement in `Vec<Recover<Statement, punct::Semi>>`.
+    ///
+    /// Recorded errors belong to the [`Parser`] (and the fork) they were recovered in, so
+    /// errors in alternatives that are backtracked out of are discarded along with them.
+    pub fn parse_recover<P: Parse, S: Parse>(&mut self) -> Recover<P, S> {
+        let mut fork = self.clone();
+        let error = match fork.parse::<P>() {
+            Ok(parsed) => {
+                *self = fork;
+                return Recover::Parsed(parsed);
+            }
+            Err(error) => error,
+        };
+        self.errors.messages.extend(error.messages.iter().cloned());
+        let mut skipped = TokenStream::new();
+        loop {
+            if let Ok(Some(sync)) = self.try_parse::<S>() {
+                return Recover::Skipped {
+                    error,
+                    skipped,
+                    sync: Some(sync),
+                };
+            }
+            let Some(token) = self.next() else {
+                break;
+            };
+            skipped.push(token);
+        }
+        Recover::Skipped {
+            error,
+            skipped,
+            sync: None,
+        }
+    }
+
+    /// Returns the index of the first error recovered from since there were `len` of them,
+    /// which is the end of the errors if a rule took them in the meantime.
+    fn recovered_since(&self, len: usize) -> usize {
+        len.min(self.errors.messages.len())
+    }
+
+    /// Returns the errors recovered from so far by [`Parser::parse_recover`].
+    pub fn errors(&self) -> &ParseError {
+        &self.errors
+    }
+
+    /// Takes the errors recovered from so far by [`Parser::parse_recover`], leaving none.
+    pub fn take_errors(&mut self) -> ParseError {
+        core::mem::take(&mut self.errors)
+    }
+
+    /// Returns `result`, unless errors were recovered from, in which case they are returned
+    /// along with the error of `result`, if any.
+    pub(crate) fn finish<R>(&mut self, result: ParseResult<R>) -> ParseResult<R> {
+        let mut errors = self.take_errors();
+        if errors.messages.is_empty() {
+            return result;
+        }
+        if let Err(error) = result {
+            errors.messages.extend(error.messages);
+        }
+        Err(errors)
+    }
+
+    /// Starts a [`Lookahead`] at the current position, for manually choosing between
+    /// alternatives while still reporting all of them on failure.
+    pub fn lookahead(&self) -> Lookahead<'_, 'a, T> {
+        Lookahead {
+            input: self,
+            alternatives: Alternatives::new(),
+        }
+    }
+
+    /// Returns `true` if the next [`TokenTree`] matches the specified [`TokenPattern`], without
+    /// consuming it.
+    pub fn peek_matches(&self, pattern: TokenPattern) -> bool {
+        self.peek()
+            .is_some_and(|token_tree| Token::from(token_tree).matches(pattern))
+    }
+
+    /// Parses a `P` if the next [`TokenTree`] matches the specified [`TokenPattern`], otherwise
+    /// consumes nothing and returns `None`.
+    pub fn parse_if<P: Parse>(&mut self, pattern: TokenPattern) -> ParseResult<Option<P>> {
+        match self.peek_matches(pattern) {
+            true => Ok(Some(self.parse()?)),
+            false => Ok(None),
+        }
+    }
+
+    /// Parses a `P` that must consume all remaining input.
+    pub fn parse_terminated<P: Parse>(&mut self) -> ParseResult<P> {
+        let parsed = self.parse::<P>()?;
+        self.parse::<Nothing>()?;
+        Ok(parsed)
+    }
+
+    /// Consumes the next [`TokenTree`], which must be a [`Group`] with the specified
+    /// [`Delimiter`].
+    pub fn parse_group(&mut self, delimiter: Delimiter) -> ParseResult<Group> {
+        let expected = TokenPattern::Delimiter(Pattern::Specific(delimiter));
+        match self.peek() {
+            Some(TokenTree::Tree(group)) if group.delimiter == delimiter => {
+                self.next();
+                Ok(group)
+            }
+            Some(token_tree) => {
+                let span = token_tree.span();
+                Err(ParseError::new().expected_token(expected, Some(token_tree.into()), span))
+            }
+            None => Err(ParseError::new().expected_token(expected, None, self.span())),
+        }
+    }
+
+    /// Parses a `P` from the content of the next [`TokenTree`], which must be a [`Group`] with
+    /// the specified [`Delimiter`]. The content must be consumed entirely.
+    pub fn parse_delimited<P: Parse>(&mut self, delimiter: Delimiter) -> ParseResult<P> {
+        let group = self.parse_group(delimiter)?;
+        self.parse_nested(&group.content, group.span_close)
+    }
+
+    /// Parses a `P` from every [`TokenTree`] up to (but not including) the next one matching the
+    /// specified [`TokenPattern`], or from the rest of the input if there is no such
+    /// [`TokenTree`]. The tokens before the terminator must be consumed entirely.
+    pub fn parse_until<P: Parse>(&mut self, pattern: TokenPattern) -> ParseResult<P> {
+        let mut tokens = TokenStream::new();
+        while let Some(token_tree) = self.peek() {
+            if Token::from(token_tree.clone()).matches(pattern) {
+                break;
+            }
+            tokens.push(token_tree);
+            self.next();
+        }
+        let end_span = self
+            .peek()
+            .map_or(self.span(), |token_tree| token_tree.span());
+        self.parse_nested(&tokens, end_span)
+    }
+
+    /// Parses a `P` consuming all of `tokens` with a nested [`Parser`] that shares the state of
+    /// this one, reporting the end of `tokens` at `end_span`.
+    pub(crate) fn parse_nested<P: Parse>(
+        &mut self,
+        tokens: &TokenStream,
+        end_span: Span,
+    ) -> ParseResult<P> {
+        let mut nested = Parser::new(tokens.iter_with_state(), end_span);
+        *nested.state_mut() = self.state().clone();
+        // cursors are only meaningful within the same tokens, so the cache can't be shared
+        if let Some(memo) = &self.memo {
+            nested.memo = Some(memo.borrow().empty());
+        }
+        let parsed = nested.parse_terminated::<P>();
+        *self.state_mut() = nested.state().clone();
+        self.committed |= nested.committed;
+        self.errors.messages.extend(nested.errors.messages);
+        parsed
     }
 }
 
 impl<'a, T: Default + Clone> From<TSIterator<'a, T>> for Parser<'a, T> {
     fn from(value: TSIterator<'a, T>) -> Self {
-        Parser(value, Span::call_site())
+        Parser::new(value, Span::call_site())
     }
 }
 
@@ -162,23 +1034,68 @@ pub trait Parse:
 {
     fn parse<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self>;
 
+    /// Parses a `Self` the way [`Parser::parse`] does. Defaults to [`Parse::parse`], while
+    /// `'static` types can override it to call [`Parser::parse_memo`] instead, so that they
+    /// are memoized by memoizing parsers (see [`Parser::memoize`]).
+    fn parse_memoized<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        Self::parse(input)
+    }
+
+    /// Describes what this type parses in error messages such as "expected at least 1 ident".
+    ///
+    /// Defaults to the name of the type.
+    fn description() -> InStr {
+        type_description::<Self>()
+    }
+
+    /// Parses a `Self` from all of `tokens` with a memoizing [`Parser`] (see
+    /// [`Parser::memoize`]), so that left-recursive rules work.
     fn parse_tokens(tokens: impl Into<TokenStream>) -> ParseResult<Self> {
+        let (parsed, errors) = Self::parse_tokens_recovering(tokens)?;
+        match errors.messages.is_empty() {
+            true => Ok(parsed),
+            false => Err(errors),
+        }
+    }
+
+    /// Like [`Parse::parse_tokens`], but returns the parsed `Self` along with the errors
+    /// recovered from while parsing it (see [`Parser::parse_recover`]) instead of failing if
+    /// there are any. If `Self` fails to parse, the recovered errors precede its error.
+    fn parse_tokens_recovering(tokens: impl Into<TokenStream>) -> ParseResult<(Self, ParseError)> {
         let tokens = tokens.into();
         let mut input: Parser = tokens.to_parser();
-        let parsed = input.parse::<Self>()?;
-        input.parse::<Nothing>()?;
-        Ok(parsed)
+        input.memoize();
+        let parsed = input.parse::<Self>().and_then(|parsed| {
+            input.parse::<Nothing>()?;
+            Ok(parsed)
+        });
+        match parsed {
+            Ok(parsed) => Ok((parsed, input.take_errors())),
+            Err(error) => input.finish(Err(error)),
+        }
     }
 
+    /// Like [`Parse::parse_tokens`], but returns the tokens after the `Self` instead of
+    /// requiring it to consume all of them.
     fn parse_tokens_compact(tokens: impl Into<TokenStream>) -> ParseResult<(Self, TokenStream)> {
         let tokens = tokens.into();
         let mut input: Parser = tokens.to_parser();
-        let parsed = input.parse::<Self>()?;
-        let remaining: TokenStream = input.into_iter().collect();
+        input.memoize();
+        let parsed = input.parse::<Self>();
+        let parsed = input.finish(parsed)?;
+        let remaining: TokenStream = input.collect();
         Ok((parsed, remaining))
     }
 }
 
+/// Returns the name of `T` without its path or generic arguments, i.e. `Rep` for
+/// `sin_types::parsed::rep::Rep<sin_types::parsed::ident::Ident>`.
+pub(crate) fn type_description<T: ?Sized>() -> InStr {
+    let name = core::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name).into()
+}
+
 impl<P: Parse> From<P> for TokenStream {
     fn from(value: P) -> Self {
         value.to_token_stream()
@@ -193,6 +1110,404 @@ pub fn parse<T: Parse>(tokens: impl Into<TokenStream>) -> ParseResult<T> {
     T::parse_tokens(tokens)
 }
 
+/// Parses a `T` from all of `tokens`, returning it along with the errors recovered from while
+/// parsing it, see [`Parse::parse_tokens_recovering`].
+pub fn parse_recovering<T: Parse>(tokens: impl Into<TokenStream>) -> ParseResult<(T, ParseError)> {
+    T::parse_tokens_recovering(tokens)
+}
+
 pub fn parse_compact<T: Parse>(tokens: impl Into<TokenStream>) -> ParseResult<(T, TokenStream)> {
     T::parse_tokens_compact(tokens)
 }
+
+#[test]
+fn test_parse_error_merge() {
+    let span = Span::new(",");
+    let a = ParseError::new().expected_token(pat![fn], Some(t![,]), span);
+    let b = ParseError::new().expected_token(pat![!ident], Some(t![,]), span);
+    let c = ParseError::new().expected_token(pat![struct], Some(t![,]), span);
+    let merged = a.merge(&b).merge(&c).merge(&a);
+    assert_eq!(merged.messages.len(), 1);
+    assert_eq!(
+        merged.messages[0],
+        "expected `fn`, ident or `struct`, found `,`"
+    );
+    let other = ParseError::new().error(span, "something else");
+    assert_eq!(merged.merge(&other).messages.len(), 2);
+    let elsewhere = ParseError::new().expected_token(pat![;], None, Span::call_site());
+    assert_eq!(merged.merge(&elsewhere).messages.len(), 2);
+    // the details of merged messages are kept, once each
+    let a = a.note("items start with a keyword").help("add `fn`");
+    let b = b
+        .note("items start with a keyword")
+        .label(span, "in this item");
+    let merged = a.merge(&b).merge(&b);
+    let message = &merged.messages[0];
+    assert_eq!(message.notes.len(), 1);
+    assert_eq!(message.help, ["add `fn`"]);
+    assert_eq!(message.labels.len(), 1);
+}
+
+#[test]
+fn test_error_message_builders() {
+    let span = Span::new("=>");
+    let message = ErrorMessage::new(span, "expected `->`, found `=>`")
+        .label(span, "here")
+        .note("return types are preceded by `->`")
+        .help("see the reference")
+        .suggestion(span, "use `->`", "->");
+    let error = ParseError::new().message(message.clone());
+    assert_eq!(error, ParseError::from(message));
+    let built = ParseError::new()
+        .error(span, "expected `->`, found `=>`")
+        .label(span, "here")
+        .note("return types are preceded by `->`")
+        .help("see the reference")
+        .suggestion(span, "use `->`", "->");
+    assert_eq!(built, error);
+}
+
+#[test]
+#[should_panic = "there are no messages"]
+fn test_parse_error_builder_without_message() {
+    let _ = ParseError::new().note("nothing to attach this to");
+}
+
+#[test]
+fn test_parse_error_to_compile_error() {
+    let tokens: TokenStream = "a \"b\"".parse().unwrap();
+    let spans: Vec<Span> = tokens.iter().map(|token| token.span()).collect();
+    let error = ParseError::new()
+        .error(spans[0], "expected `\"`")
+        .error(spans[1], "unexpected string");
+    let compile_errors = error.to_compile_error();
+    let expected: TokenStream = r#"
+        ::core::compile_error!("expected `\"`");
+        ::core::compile_error!("unexpected string");
+    "#
+    .parse()
+    .unwrap();
+    let tokens = |stream: &TokenStream| stream.iter().map(Token::from).collect::<Vec<_>>();
+    assert_eq!(tokens(&compile_errors), tokens(&expected));
+    // each invocation is spanned at its message
+    for (i, token) in compile_errors.iter().enumerate() {
+        assert_eq!(token.span(), spans[i / 7]);
+    }
+    assert!(ParseError::new().to_compile_error().is_empty());
+}
+
+#[test]
+fn test_parse_error_diagnostics() {
+    let tokens: TokenStream = "fn a() => b { a }".parse().unwrap();
+    let spans: Vec<Span> = tokens.iter().map(|token| token.span()).collect();
+    let error = ParseError::new()
+        .error(spans[3], "expected `->`, found `=>`")
+        .label(spans[0], "in this function")
+        .note("return types are preceded by `->`")
+        .help("see the reference")
+        .suggestion(spans[3], "use an arrow", "->");
+    assert_eq!(
+        error.to_string(),
+        "error: expected `->`, found `=>`
+ --> 1:8
+  |
+1 | fn a() => b { a }
+  |        ^^
+  | -- in this function
+  |
+  = note: return types are preceded by `->`
+  = help: see the reference
+help: use an arrow
+  |
+1 | fn a() -> b { a }
+  |        ~~"
+    );
+    // the primary message carries the notes, while labels and suggestions are separate
+    let compile_errors = error.to_compile_error();
+    let messages: Vec<TokenTree> = compile_errors
+        .iter()
+        .filter_map(|token| match token {
+            TokenTree::Tree(group) => group.content.iter().next(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        messages[0].as_str(),
+        "expected `->`, found `=>`\n= note: return types are preceded by `->`\n= help: see the reference"
+    );
+    assert_eq!(messages[0].span(), spans[3]);
+    assert_eq!(messages[1].as_str(), "in this function");
+    assert_eq!(messages[1].span(), spans[0]);
+    assert_eq!(messages[2].as_str(), "help: use an arrow: `->`");
+    // builders only affect the last message
+    let error = error.error(spans[4], "unexpected `b`").note("b");
+    assert_eq!(error.messages[0].notes.len(), 1);
+    assert_eq!(error.messages[1].notes.len(), 1);
+}
+
+#[test]
+fn test_alternatives() {
+    let tokens: TokenStream = "a b c".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    let mut alternatives = Alternatives::new();
+    assert!(!alternatives.peek(&input, pat![struct]));
+    let parsed = alternatives.attempt(&mut input, |input| {
+        input.parse::<Ident>()?;
+        input.parse::<Ident>()?;
+        input.parse::<punct::Comma>()
+    });
+    assert!(parsed.is_none());
+    assert_eq!(input.cursor(), 0);
+    let parsed = alternatives.attempt(&mut input, |input| input.parse::<Ident>());
+    assert_eq!(parsed.unwrap(), "a");
+    assert_eq!(input.cursor(), 1);
+    let err = alternatives.into_error(&input);
+    assert_eq!(err.messages.len(), 1);
+    assert_eq!(err.messages[0], "expected `,`, found `c`");
+}
+
+#[test]
+fn test_lookahead() {
+    let tokens: TokenStream = "impl Foo".parse().unwrap();
+    let input: Parser = tokens.to_parser();
+    let mut lookahead = input.lookahead();
+    assert!(!lookahead.peek(pat![fn]));
+    assert!(!lookahead.peek(pat![struct]));
+    assert!(!lookahead.peek(pat![enum]));
+    let err = lookahead.error();
+    assert_eq!(err.messages.len(), 1);
+    assert_eq!(
+        err.messages[0],
+        "expected `fn`, `struct` or `enum`, found `impl`"
+    );
+    assert_eq!(err.messages[0].span.source_text(), Some("impl"));
+    let mut lookahead = input.lookahead();
+    assert!(!lookahead.peek_parse::<Ident>());
+    assert!(lookahead.peek(pat![impl]));
+    assert!(lookahead.peek_parse::<(TokenTree, Ident)>());
+    assert_eq!(input.cursor(), 0);
+    let empty = TokenStream::new();
+    let input: Parser = empty.to_parser();
+    let mut lookahead = input.lookahead();
+    assert!(!lookahead.peek(pat![;]));
+    assert!(!lookahead.peek_parse::<Ident>());
+    assert_eq!(
+        lookahead.error().messages[0],
+        "expected `;` or ident, found end of tokens"
+    );
+}
+
+/// Implements [`ToTokens`] and [`Spanned`] for a test rule by delegating to the rule it wraps.
+#[cfg(test)]
+macro_rules! impl_wrapper_tokens {
+    ($ty:ty) => {
+        impl ToTokens for $ty {
+            fn to_token_stream(&self) -> TokenStream {
+                self.0.to_token_stream()
+            }
+        }
+
+        impl Spanned for $ty {
+            fn span(&self) -> Span {
+                self.0.span()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+static COUNTED_PARSES: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
+/// An [`Ident`] that counts how many times it is parsed.
+#[cfg(test)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+struct Counted(Ident);
+
+#[cfg(test)]
+impl_wrapper_tokens!(Counted);
+
+#[cfg(test)]
+impl Parse for Counted {
+    fn parse<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        COUNTED_PARSES.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
+        Ok(Counted(input.parse()?))
+    }
+
+    fn parse_memoized<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        input.parse_memo()
+    }
+}
+
+#[test]
+fn test_parser_memoize() {
+    use core::sync::atomic::Ordering::SeqCst;
+    type Choice = OneOf3<(Counted, punct::Colon), (Counted, punct::Semi), (Counted, punct::Comma)>;
+    let tokens: TokenStream = "a, (b,)".parse().unwrap();
+    COUNTED_PARSES.store(0, SeqCst);
+    let mut input: Parser = tokens.to_parser();
+    assert!(!input.is_memoized());
+    assert!(matches!(input.parse::<Choice>(), Ok(OneOf3::C(_))));
+    assert_eq!(COUNTED_PARSES.load(SeqCst), 3);
+
+    COUNTED_PARSES.store(0, SeqCst);
+    let mut input: Parser = tokens.to_parser();
+    input.memoize();
+    assert!(matches!(input.parse::<Choice>(), Ok(OneOf3::C(_))));
+    assert_eq!(COUNTED_PARSES.load(SeqCst), 1);
+    assert_eq!(input.cursor(), 2);
+    // nested parsers memoize too, with their own table
+    let nested = input.parse::<Paren<Choice>>().unwrap();
+    assert!(matches!(nested.content, OneOf3::C(_)));
+    assert_eq!(COUNTED_PARSES.load(SeqCst), 2);
+    assert!(input.memo_len() > 0);
+    input.clear_memo();
+    assert_eq!(input.memo_len(), 0);
+
+    // a cached failure is replayed with the position it failed at
+    let mut input: Parser = tokens.to_parser();
+    input.memoize_bounded(2);
+    let mut fork = input.clone();
+    assert!(input.parse::<(Ident, Ident)>().is_err());
+    assert_eq!(input.cursor(), 2);
+    assert!(fork.parse::<(Ident, Ident)>().is_err());
+    assert_eq!(fork.cursor(), 2);
+    assert!(input.memo_len() <= 2);
+
+    // rules are cached per state, and replaying them doesn't overwrite a different state
+    COUNTED_PARSES.store(0, SeqCst);
+    let mut start: Parser<u8> = tokens.to_state_parser();
+    start.memoize();
+    start.clone().parse::<Counted>().unwrap();
+    let mut other = start.clone();
+    *other.state_mut() = 1;
+    other.parse::<Counted>().unwrap();
+    assert_eq!(*other.state(), 1);
+    assert_eq!(COUNTED_PARSES.load(SeqCst), 2);
+    start.parse::<Counted>().unwrap();
+    assert_eq!(COUNTED_PARSES.load(SeqCst), 2);
+}
+
+/// An [`Ident`] whose rule takes the errors recovered from before it.
+#[cfg(test)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+struct Drained(Ident);
+
+#[cfg(test)]
+impl_wrapper_tokens!(Drained);
+
+#[cfg(test)]
+impl Parse for Drained {
+    fn parse<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        input.take_errors();
+        Ok(Drained(input.parse()?))
+    }
+
+    fn parse_memoized<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        input.parse_memo()
+    }
+}
+
+#[test]
+fn test_parser_memoize_taken_errors() {
+    let tokens: TokenStream = "; a".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    input.memoize();
+    assert!(input
+        .parse_recover::<Ident, punct::Semi>()
+        .error()
+        .is_some());
+    assert_eq!(input.errors().messages.len(), 1);
+    let mut fork = input.clone();
+    assert_eq!(input.parse::<Drained>().unwrap().0, "a");
+    assert!(input.errors().messages.is_empty());
+    // replaying the cached rule doesn't bring the taken errors back
+    assert_eq!(fork.parse::<Drained>().unwrap().0, "a");
+    assert_eq!(fork.errors().messages.len(), 1);
+}
+
+/// A rule borrowing from outside of the input, which can't be memoized.
+#[cfg(test)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+struct Borrowed<'s>(Ident, core::marker::PhantomData<&'s str>);
+
+#[cfg(test)]
+impl_wrapper_tokens!(Borrowed<'_>);
+
+#[cfg(test)]
+impl Parse for Borrowed<'_> {
+    fn parse<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        Ok(Borrowed(input.parse()?, core::marker::PhantomData))
+    }
+}
+
+#[test]
+fn test_parse_borrowed() {
+    fn parse_borrowed<'s>(tokens: &TokenStream, _: &'s str) -> Vec<Borrowed<'s>> {
+        let mut input: Parser = tokens.to_parser();
+        input.memoize();
+        input.parse().unwrap()
+    }
+    let tokens: TokenStream = "a b".parse().unwrap();
+    let source = String::from("a b");
+    let parsed = parse_borrowed(&tokens, &source);
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[1].0, "b");
+}
+
+/// `Chain := Chain "." Ident | Ident`, which is directly left-recursive.
+#[cfg(test)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+struct Chain(Either<(Box<Chain>, punct::Dot, Ident), Ident>);
+
+#[cfg(test)]
+impl_wrapper_tokens!(Chain);
+
+#[cfg(test)]
+impl Parse for Chain {
+    fn parse<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        Ok(Chain(input.parse()?))
+    }
+
+    fn parse_memoized<'a, T: Default + Clone>(input: &mut Parser<'a, T>) -> ParseResult<Self> {
+        input.parse_memo()
+    }
+}
+
+#[test]
+fn test_parser_left_recursion() {
+    fn depth(chain: &Chain) -> usize {
+        match &chain.0 {
+            Either::Left((inner, _, _)) => depth(inner) + 1,
+            Either::Right(_) => 0,
+        }
+    }
+    let tokens: TokenStream = "a.b.c.d, e".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    input.memoize();
+    let chain = input.parse::<Chain>().unwrap();
+    assert_eq!(depth(&chain), 3);
+    let Either::Left((inner, _, last)) = &chain.0 else {
+        panic!("expected a field access");
+    };
+    assert_eq!(*last, "d");
+    assert_eq!(inner.span().source_text(), Some("a.b.c"));
+    assert_eq!(chain.span().source_text(), Some("a.b.c.d"));
+    assert_eq!(input.cursor(), 7);
+    // the final result is cached, so parsing again at the same position is a lookup
+    let mut fork = tokens.to_parser();
+    fork.memo = input.memo.clone();
+    assert_eq!(fork.parse::<Chain>().unwrap(), chain);
+    assert_eq!(fork.cursor(), 7);
+
+    let tokens: TokenStream = "a.".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    input.memoize_bounded(1);
+    assert_eq!(depth(&input.parse::<Chain>().unwrap()), 0);
+    assert_eq!(input.cursor(), 1);
+    let tokens: TokenStream = ".".parse().unwrap();
+    let mut input: Parser = tokens.to_parser();
+    input.memoize();
+    let err = input.parse::<Chain>().unwrap_err();
+    assert_eq!(err.messages[0], "expected ident, found `.`");
+}
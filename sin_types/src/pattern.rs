@@ -48,6 +48,7 @@ pub enum TokenPattern {
     Punct(Pattern<Punct>),
     Keyword(Pattern<Keyword>),
     CustomKeyword(Pattern<InStr>),
+    Lifetime(Pattern<InStr>),
     Nothing,
     Wildcard,
 }
@@ -61,6 +62,7 @@ impl ToWildcard for TokenPattern {
             TokenPattern::Punct(val) => TokenPattern::Punct(val.to_wildcard()),
             TokenPattern::Keyword(val) => TokenPattern::Keyword(val.to_wildcard()),
             TokenPattern::CustomKeyword(val) => TokenPattern::CustomKeyword(val.to_wildcard()),
+            TokenPattern::Lifetime(val) => TokenPattern::Lifetime(val.to_wildcard()),
             TokenPattern::Nothing | TokenPattern::Wildcard => TokenPattern::Wildcard,
         }
     }
@@ -75,6 +77,7 @@ impl Matches<TokenPattern> for Token {
             (Token::Punct(punct), TokenPattern::Punct(pat)) => punct.matches(pat),
             (Token::Keyword(kw), TokenPattern::Keyword(pat)) => kw.matches(pat),
             (Token::CustomKeyword(ckw), TokenPattern::CustomKeyword(pat)) => ckw.matches(pat),
+            (Token::Lifetime(lt), TokenPattern::Lifetime(pat)) => lt.matches(pat),
             (_, TokenPattern::Wildcard) => true,
             _ => false,
         }
@@ -192,6 +195,7 @@ impl From<Token> for TokenPattern {
             Token::Punct(punct) => TokenPattern::Punct(Specific(punct)),
             Token::Keyword(kw) => TokenPattern::Keyword(Specific(kw)),
             Token::CustomKeyword(ckw) => TokenPattern::CustomKeyword(Specific(ckw)),
+            Token::Lifetime(lt) => TokenPattern::Lifetime(Specific(lt)),
         }
     }
 }
@@ -260,6 +264,10 @@ impl Display for TokenPattern {
                 Specific(val) => f.write_fmt(format_args!("`{val}`")),
                 Wildcard => f.write_str("custom keyword"),
             },
+            TokenPattern::Lifetime(val) => match val {
+                Specific(val) => f.write_fmt(format_args!("`{val}`")),
+                Wildcard => f.write_str("lifetime"),
+            },
             TokenPattern::Nothing => f.write_str("nothing"),
             TokenPattern::Wildcard => f.write_str("token"),
         }
@@ -369,6 +377,7 @@ macro_rules! pat {
     (*=)             => { $crate::TokenPattern::Punct($crate::Pattern::Specific($crate::Punct::StarEq)) };
     (~)              => { $crate::TokenPattern::Punct($crate::Pattern::Specific($crate::Punct::Tilde)) };
     (_)              => { $crate::TokenPattern::Punct($crate::Pattern::Specific($crate::Punct::Underscore)) };
+    ($lt:lifetime)   => { $crate::TokenPattern::Lifetime($crate::Pattern::Specific($crate::InStr::from(stringify!($lt)))) };
     ($lit:literal)   => { $crate::TokenPattern::Literal($crate::LiteralPattern::parse(stringify!($lit)).unwrap()) };
     (true)           => { $crate::TokenPattern::Literal($crate::LiteralPattern::BoolLit::True) };
     (false)          => { $crate::TokenPattern::Literal($crate::LiteralPattern::BoolLit::False) };
@@ -385,6 +394,7 @@ macro_rules! pat {
 	(!str)			 => { $crate::TokenPattern::Literal($crate::LiteralPattern::String($crate::Pattern::Wildcard)) };
 	(!byte)			 => { $crate::TokenPattern::Literal($crate::LiteralPattern::Byte($crate::Pattern::Wildcard)) };
 	(!bytestr)		 => { $crate::TokenPattern::Literal($crate::LiteralPattern::ByteString($crate::Pattern::Wildcard)) };
+    (!lifetime)      => { $crate::TokenPattern::Lifetime($crate::Pattern::Wildcard) };
     (!punct)         => { $crate::TokenPattern::Punct($crate::Pattern::Wildcard) };
     (!delim)         => { $crate::TokenPattern::Delimiter($crate::Pattern::Wildcard) };
     (!token)         => { $crate::TokenPattern::Wildcard };
@@ -419,4 +429,7 @@ fn test_token_matches() {
     assert!(t![false].matches(pat![!lit]));
     assert!(!t![something].matches(TokenPattern::Nothing));
     assert!(t![false].matches(TokenPattern::Wildcard));
+    assert!(t!['a].matches(pat!['a]));
+    assert!(t!['static].matches(pat![!lifetime]));
+    assert!(!t![#a].matches(pat![!lifetime]));
 }
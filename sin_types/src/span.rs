@@ -1,4 +1,3 @@
-extern crate proc_macro;
 use crate::*;
 use core::{fmt::Debug, hash::Hash, ops::Deref};
 use interned::{derive_from_interned_impl_value, unsafe_impl_data_type, Interned};
@@ -34,6 +33,8 @@ pub trait Span1Extensions: Sized {
     /// the input should typically come from calling [`id`](`Span1Extensions::id`) on a
     /// [`proc_macro::Span`] unless you know what you are doing.
     ///
+    /// # Safety
+    ///
     /// Unfortunately the way [`proc_macro`] internals are currently implemented, you will
     /// experience an ICE if you try to do anything with a [`proc_macro::Span`] from a
     /// no-longer-active proc macro, and that applies to this method as well.
@@ -85,6 +86,21 @@ impl SourceExcerpt {
     pub fn as_str(&self) -> &'static str {
         &self.source.as_str()[self.start..self.end]
     }
+
+    /// Returns the byte offset of the start of this excerpt within its [`source`](Self::source).
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the byte offset of the end of this excerpt within its [`source`](Self::source).
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Returns the whole source text this excerpt was taken from.
+    pub fn source(&self) -> &'static str {
+        self.source.as_str()
+    }
 }
 
 derive_staticize!(SpanData);
@@ -238,6 +254,16 @@ impl Span {
         }
     }
 
+    /// Returns where this [`Span`] is within its source text, if it uses the fallback
+    /// implementation and has any. Unlike [`Span::source_text`], this is [`None`] for
+    /// [`proc_macro::Span`]s, since their source text isn't the whole source.
+    pub fn source_excerpt(&self) -> Option<SourceExcerpt> {
+        match self.span_data() {
+            SpanData::ProcMacro(_) => None,
+            SpanData::Fallback { source_text, .. } => *source_text,
+        }
+    }
+
     /// Creates a new `Span` from the specified source string.
     ///
     /// The created span will use the fallback implementation rather than a built-in
@@ -317,7 +343,7 @@ impl Span {
                 style: SpanStyle::Normal,
                 source_text: Some(SourceExcerpt {
                     start: a_excerpt.start,
-                    end: b_excerpt.end,
+                    end: a_excerpt.end.max(b_excerpt.end),
                     source: a_excerpt.source,
                 }),
             }
@@ -360,7 +386,7 @@ impl Span {
             }
             .into()
         } else {
-            return *self;
+            *self
         }
     }
 
@@ -371,6 +397,8 @@ impl Span {
     /// the input should typically come from calling [`id`](`Span1Extensions::id`) on a
     /// [`proc_macro::Span`] unless you know what you are doing.
     ///
+    /// # Safety
+    ///
     /// Unfortunately the way [`proc_macro`] internals are currently implemented, you will
     /// experience an ICE if you try to do anything with a [`proc_macro::Span`] from a
     /// no-longer-active proc macro, and that applies to this method as well.
@@ -395,7 +423,7 @@ impl FromIterator<Span> for Span {
         let Some(mut span) = iter.next() else {
             return Span::call_site();
         };
-        while let Some(span_b) = iter.next() {
+        for span_b in iter {
             span = span.join(span_b).unwrap_or_default();
         }
         span
@@ -409,7 +437,7 @@ impl FromIterator<TokenTree> for Span {
             return Span::call_site();
         };
         let mut span = tt.span();
-        while let Some(tt) = iter.next() {
+        for tt in iter {
             span = span.join(tt.span()).unwrap_or_default();
         }
         span
@@ -423,7 +451,7 @@ impl<'a> FromIterator<&'a TokenTree> for Span {
             return Span::call_site();
         };
         let mut span = tt.span();
-        while let Some(tt) = iter.next() {
+        for tt in iter {
             span = span.join(tt.span()).unwrap_or_default();
         }
         span
@@ -4,7 +4,6 @@ use crate::{
 };
 use core::fmt::Display;
 
-extern crate proc_macro;
 use proc_macro::Span as Span1;
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
@@ -24,7 +23,7 @@ impl Spanned for TokenTree {
 
 impl ToTokens for TokenTree {
     fn to_token_stream(&self) -> TokenStream {
-        self.clone().into()
+        TokenStream::from_tokens(core::slice::from_ref(self))
     }
 }
 
@@ -37,7 +36,11 @@ impl Parse for TokenTree {
                 input.span(),
             ));
         };
-        Ok(token.into())
+        Ok(token)
+    }
+
+    fn description() -> InStr {
+        TokenPattern::Wildcard.to_string().into()
     }
 }
 
@@ -58,6 +61,7 @@ impl From<Token> for TokenTree {
             Token::Punct(punct) => TokenTree::Leaf(Token::Punct(punct), Span::new(punct)),
             Token::Keyword(kw) => TokenTree::Leaf(Token::Keyword(kw), Span::new(kw)),
             Token::CustomKeyword(st) => TokenTree::Leaf(Token::CustomKeyword(st), Span::new(st)),
+            Token::Lifetime(lt) => TokenTree::Leaf(Token::Lifetime(lt), Span::new(lt)),
         }
     }
 }
@@ -90,6 +94,7 @@ impl AsInStr for Token {
             Token::Punct(punct) => punct.in_str(),
             Token::Keyword(kw) => kw.in_str(),
             Token::CustomKeyword(in_str) => *in_str,
+            Token::Lifetime(in_str) => *in_str,
         }
     }
 }
@@ -98,6 +103,22 @@ impl Token {
     pub fn as_str(&self) -> &'static str {
         self.in_str().as_str()
     }
+
+    /// Classifies an identifier-like `word` the way sin does for source input: `_` becomes a
+    /// [`Punct`], `true`/`false` become bool [`Literal`]s, Rust keywords become [`Keyword`]s
+    /// and anything else becomes a [`Token::Ident`].
+    pub fn from_word(word: impl Into<InStr>) -> Token {
+        let word: InStr = word.into();
+        match word.as_str() {
+            "_" => t![_],
+            "true" => Token::Literal(Literal::Bool(true)),
+            "false" => Token::Literal(Literal::Bool(false)),
+            other => match Keyword::try_from(other) {
+                Ok(keyword) => Token::Keyword(keyword),
+                Err(_) => Token::Ident(word),
+            },
+        }
+    }
 }
 
 impl TokenTree {
@@ -130,6 +151,13 @@ impl TokenTree {
     }
 }
 
+impl ToTokens for Token {
+    /// Emits this [`Token`] as a single [`TokenTree`] with a [`Span::call_site()`] span.
+    fn to_token_stream(&self) -> TokenStream {
+        TokenStream::from_tokens(&[TokenTree::Leaf(*self, Span::call_site())])
+    }
+}
+
 impl Display for Token {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(self.as_str())
@@ -153,12 +181,44 @@ pub struct Group {
     pub content: TokenStream,
 }
 
+impl Group {
+    /// Creates a new [`Group`] wrapping `content` in the specified [`Delimiter`] pair, using
+    /// [`Span::call_site()`] for all of its spans.
+    pub fn new(delimiter: Delimiter, content: impl Into<TokenStream>) -> Self {
+        Group::new_spanned(Span::call_site(), delimiter, content)
+    }
+
+    /// Creates a new [`Group`] wrapping `content` in the specified [`Delimiter`] pair, using
+    /// `span` for the group as well as its opening and closing delimiters.
+    pub fn new_spanned(span: Span, delimiter: Delimiter, content: impl Into<TokenStream>) -> Self {
+        Group {
+            delimiter,
+            span,
+            span_open: span,
+            span_close: span,
+            content: content.into(),
+        }
+    }
+}
+
 impl Spanned for Group {
     fn span(&self) -> Span {
         self.span
     }
 }
 
+impl ToTokens for Group {
+    fn to_token_stream(&self) -> TokenStream {
+        TokenStream::from_tokens(&[TokenTree::Tree(self.clone())])
+    }
+}
+
+impl From<Group> for TokenTree {
+    fn from(value: Group) -> Self {
+        TokenTree::Tree(value)
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct TokenParseError {
     pub msg: InStr,
@@ -184,7 +244,12 @@ impl From<&InStr> for TokenParseError {
     }
 }
 
+/// A single token, without its [`Span`].
+///
+/// New kinds of tokens may be added as the lexer learns to distinguish them, so matches on
+/// [`Token`] outside of this crate need a wildcard arm.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[non_exhaustive]
 pub enum Token {
     Ident(InStr),
     Literal(Literal),
@@ -192,6 +257,11 @@ pub enum Token {
     Punct(Punct),
     Keyword(Keyword),
     CustomKeyword(InStr),
+    /// A lifetime or label such as `'a` or `'static`, including the leading `'`.
+    ///
+    /// Lifetimes get their own variant because `'` is not valid punctuation on its own, and
+    /// lexing `'a` as a [`Token::Ident`] would make it indistinguishable from `a`.
+    Lifetime(InStr),
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
@@ -628,6 +698,34 @@ impl Display for Punct {
     }
 }
 
+impl Punct {
+    /// Greedily glues a run of joint punctuation characters (such as those produced by
+    /// [`proc_macro::Spacing::Joint`]) into the longest possible [`Punct`]s.
+    ///
+    /// Each [`Punct`] is returned along with the char index of its first character within
+    /// `chars`. Returns a [`TokenParseError`] if some part of `chars` is not valid punctuation.
+    pub fn glue(chars: &str) -> Result<Vec<(usize, Punct)>, TokenParseError> {
+        let chars: Vec<char> = chars.chars().collect();
+        let mut puncts = Vec::new();
+        let mut i = 0;
+        'outer: while i < chars.len() {
+            for len in (1..=3.min(chars.len() - i)).rev() {
+                let candidate: String = chars[i..(i + len)].iter().collect();
+                if let Ok(punct) = Punct::try_from(candidate.as_str()) {
+                    puncts.push((i, punct));
+                    i += len;
+                    continue 'outer;
+                }
+            }
+            return Err(TokenParseError::from(InStr::from(format!(
+                "invalid punctuation `{}`",
+                chars[i]
+            ))));
+        }
+        Ok(puncts)
+    }
+}
+
 #[rustfmt::skip]
 #[macro_export]
 macro_rules! t {
@@ -730,6 +828,7 @@ macro_rules! t {
     (*=)             => { $crate::Token::Punct($crate::Punct::StarEq) };
     (~)              => { $crate::Token::Punct($crate::Punct::Tilde) };
     (_)              => { $crate::Token::Punct($crate::Punct::Underscore) };
+    ($lt:lifetime)   => { $crate::Token::Lifetime($crate::InStr::from(stringify!($lt))) };
     ($lit:literal)   => { $crate::Token::Literal($crate::Literal::parse(stringify!($lit)).unwrap()) };
     (true)           => { $crate::Token::Literal($crate::Literal::BoolLit::True) };
     (false)          => { $crate::Token::Literal($crate::Literal::BoolLit::False) };
@@ -748,6 +847,20 @@ macro_rules! assert_matches_sym {
     };
 }
 
+#[test]
+fn test_punct_glue() {
+    assert_eq!(Punct::glue("<<=").unwrap(), [(0, Punct::ShlEq)]);
+    assert_eq!(
+        Punct::glue("&&=").unwrap(),
+        [(0, Punct::AndAnd), (2, Punct::Eq)]
+    );
+    assert_eq!(
+        Punct::glue(",*").unwrap(),
+        [(0, Punct::Comma), (1, Punct::Star)]
+    );
+    assert!(Punct::glue("'").is_err());
+}
+
 #[test]
 fn test_token_traits() {
     use crate::util::*;
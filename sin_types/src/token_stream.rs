@@ -1,4 +1,9 @@
 use crate::{span::Spanned, *};
+use core::str::FromStr;
+use proc_macro::{
+    Delimiter as Delimiter1, Group as Group1, Ident as Ident1, Literal as Literal1,
+    Punct as Punct1, Spacing, TokenStream as TokenStream1, TokenTree as TokenTree1,
+};
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
 pub struct TokenStream {
@@ -17,6 +22,15 @@ impl TokenStream {
         self.tokens.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Returns the top-level [`TokenTree`]s of this [`TokenStream`] as a slice.
+    pub fn as_slice(&self) -> &[TokenTree] {
+        &self.tokens
+    }
+
     pub fn iter_with_state<T: Default + Clone>(&self) -> TSIterator<'_, T> {
         TSIterator {
             cursor: 0,
@@ -40,6 +54,35 @@ impl TokenStream {
         }
     }
 
+    /// Creates a new empty [`TokenStream`] with the specified [`Span`].
+    pub fn new_spanned(span: Span) -> TokenStream {
+        TokenStream {
+            tokens: Vec::new(),
+            span,
+        }
+    }
+
+    pub fn set_span(&mut self, span: Span) {
+        self.span = span
+    }
+
+    /// Recursively replaces the [`Span`] of this [`TokenStream`] and of every [`TokenTree`]
+    /// (including [`Group`] delimiters) within it with the specified [`Span`].
+    pub fn respan(&mut self, span: Span) {
+        self.span = span;
+        for tt in &mut self.tokens {
+            match tt {
+                TokenTree::Leaf(_, leaf_span) => *leaf_span = span,
+                TokenTree::Tree(group) => {
+                    group.span = span;
+                    group.span_open = span;
+                    group.span_close = span;
+                    group.content.respan(span);
+                }
+            }
+        }
+    }
+
     pub fn from_tokens(tokens: &[TokenTree]) -> TokenStream {
         let mut ts = TokenStream::new();
         for token in tokens {
@@ -57,7 +100,7 @@ impl TokenStream {
         self.tokens.push(token_tree.into());
     }
 
-    pub fn to_parser(&self) -> Parser {
+    pub fn to_parser(&self) -> Parser<'_> {
         Parser::new(self.iter(), self.span)
     }
 
@@ -66,6 +109,21 @@ impl TokenStream {
     }
 }
 
+impl ToTokens for TokenStream {
+    fn to_token_stream(&self) -> TokenStream {
+        self.clone()
+    }
+}
+
+impl FromStr for TokenStream {
+    type Err = ParseError;
+
+    /// Lexes the specified Rust source text into a [`TokenStream`] using a default [`Lexer`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lexer::new().lex(s)
+    }
+}
+
 impl From<&[TokenTree]> for TokenStream {
     fn from(value: &[TokenTree]) -> Self {
         TokenStream::from_tokens(value)
@@ -132,6 +190,18 @@ pub struct TSIterator<'a, T: Default + Clone = ()> {
     pub state: T,
 }
 
+impl<'a, T: Default + Clone> TSIterator<'a, T> {
+    /// Returns the index of the next [`TokenTree`] this iterator will yield.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Moves to the specified `cursor`, as previously returned by [`TSIterator::cursor`].
+    pub(crate) fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor;
+    }
+}
+
 impl<'a, T: Default + Clone> Iterator for TSIterator<'a, T> {
     type Item = TokenTree;
 
@@ -176,3 +246,292 @@ impl From<&TokenStream> for TokenStream {
         value.clone()
     }
 }
+
+/// A token tree of a foreign token stream (i.e. a [`proc_macro::TokenTree`]), as read by the
+/// conversions between foreign token streams and [`TokenStream`]s.
+pub(crate) enum ForeignToken<S> {
+    /// A group, where a `None` delimiter is an invisible group.
+    Group {
+        delimiter: Option<Delimiter>,
+        span: Span,
+        span_open: Span,
+        span_close: Span,
+        stream: S,
+    },
+    Ident(String, Span),
+    Punct {
+        ch: char,
+        joint: bool,
+        span: Span,
+    },
+    Literal(String, Span),
+}
+
+/// A foreign token stream, such as [`proc_macro::TokenStream`], that can be converted to and
+/// from a [`TokenStream`] by [`TokenStream::try_from_foreign`] and
+/// [`TokenStream::into_foreign`], which take care of gluing punctuation and lifetimes.
+pub(crate) trait ForeignTokenStream:
+    Sized + IntoIterator + FromIterator<Self::Item>
+{
+    fn read(tt: Self::Item) -> ForeignToken<Self>;
+
+    fn group(delimiter: Delimiter, content: Self, span: Span) -> Self::Item;
+
+    fn ident(name: &str, raw: bool, span: Span) -> Self::Item;
+
+    fn punct(ch: char, joint: bool, span: Span) -> Self::Item;
+
+    /// Creates a literal from any [`Literal`] but [`Literal::Bool`], which is emitted as an
+    /// identifier instead.
+    fn literal(literal: Literal, span: Span) -> Self::Item;
+}
+
+impl TokenStream {
+    /// Converts a foreign token stream into a [`TokenStream`], preserving the spans of every
+    /// token.
+    ///
+    /// Runs of joint punctuation are glued back together into multi-character [`Punct`]s
+    /// (i.e. `<<=`), a `'` joined to an identifier becomes a [`Token::Lifetime`], and invisible
+    /// groups are flattened into the surrounding stream. Literals sin cannot represent (such as
+    /// C string literals) are reported as a [`ParseError`] pointing at them.
+    pub(crate) fn try_from_foreign<S: ForeignTokenStream>(stream: S) -> ParseResult<TokenStream> {
+        let mut tokens = TokenStream::new();
+        let mut iter = stream.into_iter().map(S::read).peekable();
+        while let Some(tt) = iter.next() {
+            match tt {
+                ForeignToken::Group {
+                    delimiter: None,
+                    stream,
+                    ..
+                } => tokens.extend(TokenStream::try_from_foreign(stream)?),
+                ForeignToken::Group {
+                    delimiter: Some(delimiter),
+                    span,
+                    span_open,
+                    span_close,
+                    stream,
+                } => tokens.push(TokenTree::Tree(Group {
+                    delimiter,
+                    span,
+                    span_open,
+                    span_close,
+                    content: TokenStream::try_from_foreign(stream)?,
+                })),
+                ForeignToken::Ident(word, span) => {
+                    tokens.push(TokenTree::Leaf(Token::from_word(word), span))
+                }
+                ForeignToken::Literal(text, span) => {
+                    let text = match text.strip_prefix('-') {
+                        Some(text) => {
+                            tokens.push(TokenTree::Leaf(t![-], span));
+                            text
+                        }
+                        None => &text,
+                    };
+                    let literal = Literal::parse(text).map_err(|err| {
+                        let message = format!("unsupported literal `{text}`: {err}");
+                        ParseError::new().error(span, message)
+                    })?;
+                    tokens.push(TokenTree::Leaf(Token::Literal(literal), span));
+                }
+                ForeignToken::Punct {
+                    ch: '\'',
+                    joint: true,
+                    ..
+                } if matches!(iter.peek(), Some(ForeignToken::Ident(..))) => {
+                    let Some(ForeignToken::Ident(name, span)) = iter.next() else {
+                        unreachable!("just peeked an ident");
+                    };
+                    let lifetime = InStr::from(format!("'{name}"));
+                    tokens.push(TokenTree::Leaf(Token::Lifetime(lifetime), span));
+                }
+                ForeignToken::Punct {
+                    ch,
+                    mut joint,
+                    span,
+                } => {
+                    let mut chars = String::from(ch);
+                    let mut spans = vec![span];
+                    while joint {
+                        let Some(&ForeignToken::Punct {
+                            ch,
+                            joint: next_joint,
+                            span,
+                        }) = iter.peek()
+                        else {
+                            break;
+                        };
+                        if ch == '\'' {
+                            break;
+                        }
+                        chars.push(ch);
+                        spans.push(span);
+                        joint = next_joint;
+                        iter.next();
+                    }
+                    let puncts = Punct::glue(&chars).map_err(|err| {
+                        let message = format!("unsupported punctuation `{chars}`: {}", err.msg);
+                        ParseError::new().error(__private::join_spans(&spans), message)
+                    })?;
+                    for (i, punct) in puncts {
+                        tokens.push(TokenTree::Leaf(Token::Punct(punct), spans[i]));
+                    }
+                }
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Converts this [`TokenStream`] into a foreign token stream, splitting multi-character
+    /// [`Punct`]s and lifetimes into joint punctuation and identifiers.
+    pub(crate) fn into_foreign<S: ForeignTokenStream>(self) -> S {
+        let mut tokens = Vec::new();
+        for tt in self.tokens {
+            push_foreign::<S>(&mut tokens, tt);
+        }
+        tokens.into_iter().collect()
+    }
+}
+
+fn push_foreign<S: ForeignTokenStream>(tokens: &mut Vec<S::Item>, tt: TokenTree) {
+    let (token, span) = match tt {
+        TokenTree::Leaf(token, span) => (token, span),
+        TokenTree::Tree(group) => {
+            let content = group.content.into_foreign();
+            tokens.push(S::group(group.delimiter, content, group.span));
+            return;
+        }
+    };
+    match token {
+        Token::Ident(ident) => match ident.as_str().strip_prefix("r#") {
+            Some(raw) => tokens.push(S::ident(raw, true, span)),
+            None => tokens.push(S::ident(ident.as_str(), false, span)),
+        },
+        Token::Keyword(keyword) => tokens.push(S::ident(keyword.into(), false, span)),
+        Token::CustomKeyword(word) => tokens.push(S::ident(word.as_str(), false, span)),
+        Token::Lifetime(lifetime) => {
+            tokens.push(S::punct('\'', true, span));
+            tokens.push(S::ident(&lifetime.as_str()[1..], false, span));
+        }
+        Token::Punct(Punct::Underscore) => tokens.push(S::ident("_", false, span)),
+        Token::Punct(punct) => {
+            let chars: &'static str = punct.into();
+            let last = chars.len() - 1;
+            for (i, c) in chars.chars().enumerate() {
+                tokens.push(S::punct(c, i != last, span));
+            }
+        }
+        Token::Literal(Literal::Bool(value)) => {
+            tokens.push(S::ident(if value { "true" } else { "false" }, false, span))
+        }
+        Token::Literal(literal) => tokens.push(S::literal(literal, span)),
+        Token::Delimiter(delimiter) => {
+            tokens.push(S::group(delimiter, S::from_iter(None), span));
+        }
+    }
+}
+
+impl ForeignTokenStream for TokenStream1 {
+    fn read(tt: TokenTree1) -> ForeignToken<Self> {
+        match tt {
+            TokenTree1::Group(group) => ForeignToken::Group {
+                delimiter: match group.delimiter() {
+                    Delimiter1::Parenthesis => Some(Delimiter::Paren),
+                    Delimiter1::Brace => Some(Delimiter::Brace),
+                    Delimiter1::Bracket => Some(Delimiter::Bracket),
+                    Delimiter1::None => None,
+                },
+                span: group.span().into(),
+                span_open: group.span_open().into(),
+                span_close: group.span_close().into(),
+                stream: group.stream(),
+            },
+            TokenTree1::Ident(ident) => ForeignToken::Ident(ident.to_string(), ident.span().into()),
+            TokenTree1::Punct(punct) => ForeignToken::Punct {
+                ch: punct.as_char(),
+                joint: punct.spacing() == Spacing::Joint,
+                span: punct.span().into(),
+            },
+            TokenTree1::Literal(literal) => {
+                ForeignToken::Literal(literal.to_string(), literal.span().into())
+            }
+        }
+    }
+
+    fn group(delimiter: Delimiter, content: Self, span: Span) -> TokenTree1 {
+        let delimiter = match delimiter {
+            Delimiter::Paren => Delimiter1::Parenthesis,
+            Delimiter::Brace => Delimiter1::Brace,
+            Delimiter::Bracket => Delimiter1::Bracket,
+        };
+        let mut group1 = Group1::new(delimiter, content);
+        group1.set_span(span.into());
+        group1.into()
+    }
+
+    fn ident(name: &str, raw: bool, span: Span) -> TokenTree1 {
+        match raw {
+            true => Ident1::new_raw(name, span.into()).into(),
+            false => Ident1::new(name, span.into()).into(),
+        }
+    }
+
+    fn punct(ch: char, joint: bool, span: Span) -> TokenTree1 {
+        let spacing = if joint {
+            Spacing::Joint
+        } else {
+            Spacing::Alone
+        };
+        let mut punct1 = Punct1::new(ch, spacing);
+        punct1.set_span(span.into());
+        punct1.into()
+    }
+
+    fn literal(literal: Literal, span: Span) -> TokenTree1 {
+        let mut literal1 = match literal {
+            Literal::Char(c) => Literal1::character(c),
+            Literal::String(string) => Literal1::string(string.as_str()),
+            _ => literal
+                .in_str()
+                .as_str()
+                .parse()
+                .expect("litrs literals are always valid Rust literals"),
+        };
+        literal1.set_span(span.into());
+        literal1.into()
+    }
+}
+
+impl TokenStream {
+    /// Converts a [`proc_macro::TokenStream`] into a [`TokenStream`], preserving the original
+    /// compiler spans of every token.
+    ///
+    /// Runs of [`Spacing::Joint`] punctuation are glued back together into multi-character
+    /// [`Punct`]s (i.e. `<<=`), a `'` joined to an identifier becomes a [`Token::Lifetime`], and
+    /// invisible (`Delimiter::None`) groups are flattened into the surrounding stream. Tokens
+    /// sin cannot represent, such as C string literals, result in a [`ParseError`] that can be
+    /// reported with [`ParseError::to_compile_error_proc_macro`].
+    pub fn try_from_proc_macro(tokens: TokenStream1) -> ParseResult<TokenStream> {
+        TokenStream::try_from_foreign(tokens)
+    }
+}
+
+impl From<TokenStream1> for TokenStream {
+    /// Converts a [`proc_macro::TokenStream`] into a [`TokenStream`] like
+    /// [`TokenStream::try_from_proc_macro`], except that tokens sin cannot represent turn the
+    /// result into `compile_error!` invocations spanned at them, so that expanding it reports
+    /// the error.
+    fn from(value: TokenStream1) -> Self {
+        TokenStream::try_from_proc_macro(value).unwrap_or_else(|err| err.to_compile_error())
+    }
+}
+
+impl From<TokenStream> for TokenStream1 {
+    /// Converts a [`TokenStream`] into a [`proc_macro::TokenStream`].
+    ///
+    /// Tokens that originated from proc macro input keep their original compiler spans, while
+    /// tokens using fallback [`Span`]s are emitted with call-site (or mixed-site) resolution.
+    fn from(value: TokenStream) -> Self {
+        value.into_foreign()
+    }
+}
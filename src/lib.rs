@@ -26,3 +26,7 @@ pub use sin_types as types;
 pub use sin_types::*;
 
 pub use sin_macros::*;
+pub use sin_macros::{
+    sin_proc_macro as proc_macro, sin_proc_macro_attribute as proc_macro_attribute,
+    sin_proc_macro_derive as proc_macro_derive,
+};
@@ -0,0 +1,337 @@
+use sin::*;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+pub struct TypedField {
+    /// The name of the field.
+    pub name: Ident,
+    colon: punct::Colon,
+    pub(crate) ty: Ident,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct Assignment {
+    field: TypedField,
+    eq: punct::Eq,
+    value: TokenTree,
+    semi: punct::Semi,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct Pair<T: Clone, const N: usize, S = punct::Comma>(T, S, T)
+where
+    T: core::fmt::Debug;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct Empty;
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct Path {
+    leading: punct::PathSep,
+    segments: Rep<Ident, punct::PathSep>,
+}
+
+#[test]
+fn test_derive_parse_named() {
+    let tokens: TokenStream = "my_field: MyType".parse().unwrap();
+    let field = parse::<TypedField>(&tokens).unwrap();
+    assert_eq!(field.name, "my_field");
+    assert_eq!(field.ty, "MyType");
+    assert_eq!(field.colon.span.source_text(), Some(":"));
+    assert_eq!(field.span().source_text(), Some("my_field: MyType"));
+    let round_trip = field.to_token_stream();
+    assert_eq!(round_trip.len(), 3);
+    assert_eq!(parse::<TypedField>(round_trip).unwrap(), field);
+}
+
+#[test]
+fn test_derive_parse_nested() {
+    let tokens: TokenStream = "x: u8 = { 1 + 2 };".parse().unwrap();
+    let assignment = parse::<Assignment>(&tokens).unwrap();
+    assert_eq!(assignment.field.name, "x");
+    assert!(matches!(assignment.value, TokenTree::Tree(_)));
+    assert_eq!(assignment.to_token_stream().len(), 6);
+    assert_eq!(assignment.span().source_text(), Some("x: u8 = { 1 + 2 };"));
+}
+
+#[test]
+fn test_derive_parse_tuple_and_unit() {
+    let tokens: TokenStream = "a, b".parse().unwrap();
+    let pair = parse::<Pair<Ident, 3>>(&tokens).unwrap();
+    assert_eq!(pair.0, "a");
+    assert_eq!(pair.2, "b");
+    assert_eq!(pair.to_token_stream().len(), 3);
+    let tokens: TokenStream = "a :: b".parse().unwrap();
+    let pair = parse::<Pair<Ident, 0, punct::PathSep>>(&tokens).unwrap();
+    assert_eq!(pair.span().source_text(), Some("a :: b"));
+    assert!(parse::<Empty>(TokenStream::new()).is_ok());
+    assert!(Empty.to_token_stream().is_empty());
+    assert!(Empty.span().is_call_site());
+}
+
+#[test]
+fn test_derive_parse_rep_field() {
+    let tokens: TokenStream = "::std::vec::Vec".parse().unwrap();
+    let path = parse::<Path>(&tokens).unwrap();
+    assert_eq!(path.segments.items().len(), 3);
+    assert_eq!(path.segments.items()[2], "Vec");
+    assert_eq!(parse::<Path>(path.to_token_stream()).unwrap(), path);
+    let err = parse::<Rep1<TypedField, punct::Comma>>(TokenStream::new()).unwrap_err();
+    assert_eq!(err.messages[0], "expected at least 1 TypedField");
+}
+
+#[test]
+fn test_derive_parse_errors() {
+    let tokens: TokenStream = "my_field MyType".parse().unwrap();
+    let err = parse::<TypedField>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `:`, found `MyType`");
+    assert_eq!(err.messages[0].span.source_text(), Some("MyType"));
+    let tokens: TokenStream = "x: u8 = 5".parse().unwrap();
+    let err = parse::<Assignment>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `;`, found end of tokens");
+    let tokens: TokenStream = "a, b, c".parse().unwrap();
+    assert!(parse::<Pair<Ident, 1>>(&tokens).is_err());
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+enum Declaration {
+    #[sin(peek = pat![fn])]
+    Fn(TokenTree, Ident, TokenTree),
+    #[sin(peek = pat![struct])]
+    Struct {
+        keyword: TokenTree,
+        name: Ident,
+    },
+    Typed(TypedField),
+    Name(Ident),
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+enum Member {
+    Typed(TypedField),
+    #[sin(peek = pat![fn])]
+    Fn(TokenTree, Ident, TokenTree),
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[test]
+fn test_derive_parse_enum_ordered() {
+    let decl = parse::<Declaration>("fn foo ()".parse::<TokenStream>().unwrap()).unwrap();
+    assert!(matches!(decl, Declaration::Fn(_, name, _) if name == "foo"));
+    assert_eq!(decl.to_token_stream().len(), 3);
+    let decl = parse::<Declaration>("struct Foo".parse::<TokenStream>().unwrap()).unwrap();
+    assert!(matches!(decl, Declaration::Struct { name, .. } if name == "Foo"));
+    assert_eq!(decl.span().source_text(), Some("struct Foo"));
+    let decl = parse::<Declaration>("a: B".parse::<TokenStream>().unwrap()).unwrap();
+    assert!(matches!(decl, Declaration::Typed(_)));
+    let decl = parse::<Declaration>("a".parse::<TokenStream>().unwrap()).unwrap();
+    assert!(matches!(decl, Declaration::Name(_)));
+    let tokens: TokenStream = "a b".parse().unwrap();
+    let mut input = tokens.to_parser();
+    assert!(matches!(
+        input.parse::<Declaration>(),
+        Ok(Declaration::Name(_))
+    ));
+    let either = parse::<Either<punct::Comma, Ident>>("x".parse::<TokenStream>().unwrap());
+    assert!(matches!(either, Ok(Either::Right(_))));
+}
+
+#[test]
+fn test_derive_parse_enum_errors() {
+    let tokens: TokenStream = ",".parse().unwrap();
+    let err = parse::<Declaration>(&tokens).unwrap_err();
+    assert_eq!(err.messages.len(), 1);
+    assert_eq!(
+        err.messages[0],
+        "expected `fn`, `struct` or ident, found `,`"
+    );
+    assert_eq!(err.messages[0].found, Some(t![,]));
+    assert_eq!(err.messages[0].expected.len(), 3);
+    // peeked variants are committed to, so errors within them are reported directly
+    let tokens: TokenStream = "fn 5".parse().unwrap();
+    let err = parse::<Declaration>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected ident, found `5`");
+    // the variant that made the most progress is reported
+    let tokens: TokenStream = "a: 5".parse().unwrap();
+    let err = parse::<Member>(&tokens).unwrap_err();
+    assert_eq!(err.messages.len(), 1);
+    assert_eq!(err.messages[0], "expected ident, found `5`");
+    let err = parse::<Member>(",".parse::<TokenStream>().unwrap()).unwrap_err();
+    assert_eq!(err.messages[0], "expected ident or `fn`, found `,`");
+    // the first variant that matches is committed to, even if later variants would consume
+    // more tokens
+    let err = parse::<Declaration>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected nothing, found `:`");
+    let err = parse::<Declaration>(TokenStream::new()).unwrap_err();
+    assert_eq!(
+        err.messages[0],
+        "expected `fn`, `struct` or ident, found end of tokens"
+    );
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct ReturnType {
+    arrow: punct::RArrow,
+    ty: Ident,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct Signature {
+    fn_token: TokenTree,
+    name: Ident,
+    #[sin(paren)]
+    arg: TypedField,
+    #[sin(optional_if = pat![->])]
+    ret: Option<ReturnType>,
+    #[sin(brace)]
+    body: Rep<Assignment>,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct Attribute(punct::Pound, #[sin(bracket)] Ident);
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct Import {
+    use_token: TokenTree,
+    #[sin(until = pat![;])]
+    path: Rep<Ident, punct::PathSep>,
+    semi: punct::Semi,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+struct Trailing {
+    name: Ident,
+    #[sin(terminated)]
+    field: TypedField,
+}
+
+#[test]
+fn test_derive_parse_delimited_fields() {
+    let tokens: TokenStream = "fn foo(a: B) -> C { x: u8 = 1; }".parse().unwrap();
+    let sig = parse::<Signature>(&tokens).unwrap();
+    assert_eq!(sig.arg.name, "a");
+    assert_eq!(sig.ret.as_ref().unwrap().ty, "C");
+    assert_eq!(sig.body.items().len(), 1);
+    let round_trip = sig.to_token_stream();
+    assert_eq!(round_trip.len(), 6);
+    // the groups are re-emitted spanning their content
+    let Some(TokenTree::Tree(group)) = round_trip.iter().nth(2) else {
+        panic!("expected a group, got {round_trip:?}");
+    };
+    assert_eq!(group.delimiter, Delimiter::Paren);
+    assert_eq!(group.span().source_text(), Some("a: B"));
+    assert_eq!(parse::<Signature>(round_trip).unwrap(), sig);
+    let tokens: TokenStream = "fn foo(a: B) {}".parse().unwrap();
+    let sig = parse::<Signature>(&tokens).unwrap();
+    assert!(sig.ret.is_none());
+    assert!(sig.body.items().is_empty());
+    let tokens: TokenStream = "#[inline]".parse().unwrap();
+    let attr = parse::<Attribute>(&tokens).unwrap();
+    assert_eq!(attr.1, "inline");
+    let round_trip = attr.to_token_stream();
+    assert_eq!(round_trip.len(), 2);
+    let Some(TokenTree::Tree(group)) = round_trip.iter().nth(1) else {
+        panic!("expected a group, got {round_trip:?}");
+    };
+    assert_eq!(group.delimiter, Delimiter::Bracket);
+    assert_eq!(group.span().source_text(), Some("inline"));
+    assert_eq!(parse::<Attribute>(round_trip).unwrap(), attr);
+}
+
+#[test]
+fn test_derive_parse_delimited_errors() {
+    let tokens: TokenStream = "fn foo[a: B] {}".parse().unwrap();
+    let err = parse::<Signature>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected parenthesis, found `[]`");
+    // the content of a group must be consumed entirely
+    let tokens: TokenStream = "fn foo(a: B C) {}".parse().unwrap();
+    let err = parse::<Signature>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected nothing, found `C`");
+    let tokens: TokenStream = "fn foo(a) {}".parse().unwrap();
+    let err = parse::<Signature>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `:`, found end of tokens");
+    assert_eq!(err.messages[0].span.source_text(), Some(")"));
+}
+
+#[test]
+fn test_derive_parse_until_and_terminated() {
+    let tokens: TokenStream = "use std::vec::Vec;".parse().unwrap();
+    let import = parse::<Import>(&tokens).unwrap();
+    assert_eq!(import.path.items().len(), 3);
+    assert_eq!(import.semi.span.source_text(), Some(";"));
+    assert_eq!(import.to_token_stream().len(), 7);
+    assert_eq!(parse::<Import>(import.to_token_stream()).unwrap(), import);
+    let tokens: TokenStream = "use std::vec::Vec".parse().unwrap();
+    let err = parse::<Import>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `;`, found end of tokens");
+    let tokens: TokenStream = "use std::vec Vec;".parse().unwrap();
+    let err = parse::<Import>(&tokens).unwrap_err();
+    assert_eq!(err.messages[0], "expected `::`, found `Vec`");
+    let tokens: TokenStream = "a b: C".parse().unwrap();
+    let trailing = parse::<Trailing>(&tokens).unwrap();
+    assert_eq!(trailing.field.ty, "C");
+    let tokens: TokenStream = "a b: C d".parse().unwrap();
+    let mut input = tokens.to_parser();
+    let err = input.parse::<Trailing>().unwrap_err();
+    assert_eq!(err.messages[0], "expected nothing, found `d`");
+}
+
+/// `Postfix := Postfix "." Ident | Postfix "(" ")" | Ident`
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+enum Postfix {
+    Field(Box<Postfix>, punct::Dot, Ident),
+    Call(Box<Postfix>, Paren<Nothing>),
+    Name(Ident),
+}
+
+#[test]
+fn test_derive_parse_left_recursive() {
+    let tokens: TokenStream = "a.b().c".parse().unwrap();
+    let mut input = tokens.to_parser();
+    input.memoize();
+    let postfix = input.parse::<Postfix>().unwrap();
+    let Postfix::Field(call, _, c) = &postfix else {
+        panic!("expected a field access, got {postfix:?}");
+    };
+    assert_eq!(*c, "c");
+    let Postfix::Call(field, _) = call.as_ref() else {
+        panic!("expected a call, got {call:?}");
+    };
+    assert!(
+        matches!(field.as_ref(), Postfix::Field(a, _, b) if *b == "b" && matches!(a.as_ref(), Postfix::Name(_)))
+    );
+    assert_eq!(postfix.span().source_text(), Some("a.b().c"));
+    assert_eq!(postfix.to_token_stream().len(), 6);
+    // the top-level entry points memoize without being asked to
+    assert_eq!(parse::<Postfix>(&tokens).unwrap(), postfix);
+    let (parsed, rest) = parse_compact::<Postfix>(quote!(a.b c)).unwrap();
+    assert!(matches!(parsed, Postfix::Field(..)));
+    assert_eq!(rest.len(), 1);
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Parse)]
+enum Statement {
+    /// Once the `=` is parsed, the statement must be an assignment.
+    Assign(Ident, punct::Eq, Cut, Ident, punct::Semi),
+    Name(Ident),
+}
+
+#[test]
+fn test_derive_parse_cut() {
+    let tokens: TokenStream = "a = b;".parse().unwrap();
+    let statement = parse::<Statement>(&tokens).unwrap();
+    assert!(matches!(statement, Statement::Assign(..)));
+    assert_eq!(statement.to_token_stream().len(), 4);
+    let tokens: TokenStream = "a = ;".parse().unwrap();
+    let err = parse_compact::<Statement>(&tokens).unwrap_err();
+    assert_eq!(err.messages.len(), 1);
+    assert_eq!(err.messages[0], "expected ident, found `;`");
+    let tokens: TokenStream = "a b".parse().unwrap();
+    let (statement, _) = parse_compact::<Statement>(&tokens).unwrap();
+    assert!(matches!(statement, Statement::Name(_)));
+}
@@ -0,0 +1,29 @@
+#![cfg(test)]
+
+use test_macros::*;
+
+sin_unit_structs!(Foo Bar);
+
+#[sin_with_const(ANSWER)]
+#[allow(unused)]
+struct Baz;
+
+#[derive(SinNameLen)]
+#[allow(unused)]
+struct Qux;
+
+#[test]
+fn test_proc_macro_entry_point() {
+    let (_, _) = (Foo, Bar);
+}
+
+#[test]
+fn test_proc_macro_attribute_entry_point() {
+    assert_eq!(ANSWER, 1);
+    let _ = Baz;
+}
+
+#[test]
+fn test_proc_macro_derive_entry_point() {
+    assert_eq!(Qux::NAME_LEN, 3);
+}
@@ -0,0 +1,180 @@
+use sin::*;
+
+fn render(tokens: &TokenStream) -> String {
+    tokens
+        .iter()
+        .map(|tt| match tt {
+            TokenTree::Leaf(token, _) => token.to_string(),
+            TokenTree::Tree(group) => format!(
+                "{}{}{}",
+                group.delimiter.open(),
+                render(&group.content),
+                group.delimiter.close()
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn test_quote_basic() {
+    let tokens = quote!(
+        pub struct Foo<'a> {
+            bar: &'a u8,
+            baz: [u8; 4],
+        }
+    );
+    assert_eq!(
+        render(&tokens),
+        "pub struct Foo < 'a > {bar : & 'a u8 , baz : [u8 ; 4] ,}"
+    );
+    assert_eq!(tokens.len(), 7);
+    assert!(matches!(
+        tokens.iter().last(),
+        Some(TokenTree::Tree(Group {
+            delimiter: Delimiter::Brace,
+            ..
+        }))
+    ));
+    assert!(tokens.iter().all(|tt| tt.span().is_call_site()));
+    assert!(quote!().is_empty());
+}
+
+#[test]
+fn test_quote_interpolation() {
+    let name = Ident::new("MyStruct");
+    let ty = quote!(Vec<u8>);
+    let tokens = quote! {
+        impl #name {
+            fn new() -> #name {
+                #name { inner: #ty::new() }
+            }
+        }
+    };
+    assert_eq!(
+        render(&tokens),
+        "impl MyStruct {fn new () -> MyStruct {MyStruct {inner : Vec < u8 > :: new ()}}}"
+    );
+    let nested = quote!(x <<= #tokens);
+    assert_eq!(nested.len(), 5);
+    let literal = quote!(#[doc = "docs"] # ! [allow(unused)]);
+    assert_eq!(render(&literal), "# [doc = docs] # ! [allow (unused)]");
+}
+
+#[test]
+fn test_quote_repetition() {
+    let fields = vec![Ident::new("a"), Ident::new("b"), Ident::new("c")];
+    let types = [quote!(u8), quote!(bool), quote!(String)];
+    let name = Ident::new("Foo");
+    let tokens = quote! {
+        struct #name { #(#fields: #types),* }
+        fn names() -> [&'static str; 3] { [#(stringify!(#fields)),*] }
+        #(impl Marker for #name<#types> {})*
+    };
+    assert_eq!(
+        render(&tokens),
+        "struct Foo {a : u8 , b : bool , c : String} \
+        fn names () -> [& 'static str ; 3] \
+        {[stringify ! (a) , stringify ! (b) , stringify ! (c)]} \
+        impl Marker for Foo < u8 > {} \
+        impl Marker for Foo < bool > {} \
+        impl Marker for Foo < String > {}"
+    );
+    let empty: Vec<Ident> = Vec::new();
+    assert!(quote!(#(#empty)=>*).is_empty());
+    let arms = quote!(#(#fields)=>*);
+    assert_eq!(render(&arms), "a => b => c");
+    let iter = fields.iter().rev();
+    assert_eq!(render(&quote!(#(#iter)*)), "c b a");
+    // collections are ToTokens too, so they can be interpolated whole outside a repetition
+    let whole = quote!(#fields; #(#fields #name)*);
+    assert_eq!(render(&whole), "a b c ; a Foo b Foo c Foo");
+}
+
+#[test]
+fn test_quote_repetition_sources() {
+    let fields = vec![Ident::new("a"), Ident::new("b")];
+    // iterators are cloned, so the same one can be used in several repetitions
+    let upper = fields
+        .iter()
+        .map(|ident| Ident::new(ident.ident.to_uppercase()));
+    let tokens = quote!(#(#upper)* ; #(#upper)*);
+    assert_eq!(render(&tokens), "A B ; A B");
+    assert_eq!(upper.count(), 2);
+    // collections are iterated by reference, whether or not they are ToTokens
+    let set: std::collections::BTreeSet<u8> = [2, 1].into_iter().collect();
+    let slice: &[Ident] = &fields;
+    let by_ref = &&fields;
+    let tokens = quote!(#(#set #slice #by_ref)*);
+    assert_eq!(render(&tokens), "1u8 a a 2u8 b b");
+    let array = [quote!(x), quote!(y)];
+    let tokens = quote!(#(#array)+*);
+    assert_eq!(render(&tokens), "x + y");
+    assert_eq!(fields.len(), 2);
+    // collections that are ToTokens are still interpolated whole outside of a repetition
+    let name = Ident::new("Foo");
+    let tokens = quote!(#(#name #fields: #fields),*);
+    assert_eq!(render(&tokens), "Foo a : a , Foo b : b");
+    assert_eq!(render(&quote!(#fields)), "a b");
+}
+
+#[test]
+fn test_quote_token_kinds() {
+    let tokens = quote!(r#type 'a: 'x' "a \"b\"\n" b'c' b"d" 1u8 -2.5e3 true _ <<= ..= async);
+    let expected: TokenStream =
+        r#"r#type 'a: 'x' "a \"b\"\n" b'c' b"d" 1u8 -2.5e3 true _ <<= ..= async"#
+            .parse()
+            .unwrap();
+    let tokens: Vec<Token> = tokens.iter().map(Token::from).collect();
+    let expected: Vec<Token> = expected.iter().map(Token::from).collect();
+    assert_eq!(tokens, expected);
+}
+
+#[test]
+fn test_quote_keeps_interpolated_spans() {
+    let span = Span::new("my_ident");
+    let ident = Ident::new_spanned(span, "my_ident");
+    let tokens = quote!(let #ident = 5;);
+    let spans: Vec<Span> = tokens.iter().map(|tt| tt.span()).collect();
+    assert_eq!(spans[1], span);
+    assert!(spans[0].is_call_site());
+    assert!(spans[2].is_call_site());
+}
+
+mod quoted_consts {
+    test_macros::sin_quote_consts!(FIRST, SECOND, THIRD);
+}
+
+#[test]
+fn test_quote_in_proc_macro() {
+    assert_eq!(quoted_consts::FIRST, 0);
+    assert_eq!(quoted_consts::THIRD, 2);
+}
+
+#[test]
+fn test_quote_spanned() {
+    let span = Span::new("user input");
+    let ident = Ident::new("my_var");
+    let tokens = quote_spanned!(span => let #ident: [u8; 2] = (1, 2););
+    assert_eq!(render(&tokens), "let my_var : [u8 ; 2] = (1 , 2) ;");
+    for tt in tokens.iter() {
+        match tt {
+            TokenTree::Tree(group) => {
+                assert_eq!(group.span, span);
+                assert_eq!(group.span_open, span);
+                assert_eq!(group.span_close, span);
+                assert!(group.content.iter().all(|tt| tt.span() == span));
+            }
+            TokenTree::Leaf(Token::Ident(_), ident_span) => assert!(ident_span.is_call_site()),
+            TokenTree::Leaf(_, leaf_span) => assert_eq!(leaf_span, span),
+        }
+    }
+    let idents = vec![ident, ident];
+    let mixed = quote_spanned!(Span::mixed_site() => #(#idents)*);
+    assert_eq!(mixed.len(), 2);
+    assert!(mixed.iter().all(|tt| tt.span().is_call_site()));
+    let mixed = quote_spanned!(Span::mixed_site() => let x = 5;);
+    assert!(mixed
+        .iter()
+        .all(|tt| tt.span() == Span::mixed_site() && !tt.span().is_call_site()));
+}
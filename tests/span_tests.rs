@@ -37,3 +37,20 @@ fn test_span_new_fallback() {
     let span = Span::new("pub fn foo() {}");
     assert!(span.is_fallback());
 }
+
+#[test]
+fn test_span_join_nested() {
+    let tokens: TokenStream = "a (b c) d".parse().unwrap();
+    let spans: Vec<Span> = tokens.iter().map(|tt| tt.span()).collect();
+    let outer = spans[0].join(spans[2]).unwrap();
+    assert_eq!(outer.source_text(), Some("a (b c) d"));
+    // joining with a span contained in the joined span keeps its end
+    assert_eq!(
+        outer.join(spans[1]).unwrap().source_text(),
+        Some("a (b c) d")
+    );
+    assert_eq!(
+        spans[1].join(outer).unwrap().source_text(),
+        Some("a (b c) d")
+    );
+}
@@ -4,8 +4,7 @@ use sin::span::*;
 
 #[proc_macro]
 pub fn span_from_span1(tokens: TokenStream) -> TokenStream {
-    let mut iter = tokens.into_iter();
-    while let Some(tt) = iter.next() {
+    for tt in tokens {
         let span1 = tt.span();
         let span: Span = span1.into();
         let span_source_text: Option<String> = span.source_text().map(|st| st.into());
@@ -17,8 +16,7 @@ pub fn span_from_span1(tokens: TokenStream) -> TokenStream {
 
 #[proc_macro]
 pub fn span_round_trip(tokens: TokenStream) -> TokenStream {
-    let mut iter = tokens.into_iter();
-    while let Some(tt) = iter.next() {
+    for tt in tokens {
         let span1 = tt.span();
         let span: Span = span1.into();
         let roundtrip: Span1 = span.into();
@@ -27,3 +25,105 @@ pub fn span_round_trip(tokens: TokenStream) -> TokenStream {
     }
     "".parse().unwrap()
 }
+
+#[proc_macro]
+pub fn token_stream_round_trip(tokens: TokenStream) -> TokenStream {
+    let original: String = tokens.to_string().split_whitespace().collect();
+    let sin_tokens: sin::TokenStream = tokens.into();
+    let round_trip: TokenStream = sin_tokens.into();
+    let round_trip: String = round_trip.to_string().split_whitespace().collect();
+    assert_eq!(round_trip, original);
+    "".parse().unwrap()
+}
+
+#[proc_macro]
+pub fn sin_identity(tokens: TokenStream) -> TokenStream {
+    let sin_tokens: sin::TokenStream = tokens.into();
+    sin_tokens.into()
+}
+
+/// Expands to the message of the error converting the input into a sin `TokenStream`, if any.
+#[proc_macro]
+pub fn sin_conversion_error(tokens: TokenStream) -> TokenStream {
+    let message = match sin::TokenStream::try_from_proc_macro(tokens) {
+        Ok(_) => String::new(),
+        Err(err) => err.messages[0].message.to_string(),
+    };
+    format!("{message:?}").parse().unwrap()
+}
+
+/// Expands to the text of the input after an infallible conversion into a sin `TokenStream`.
+#[proc_macro]
+pub fn sin_lossy_conversion(tokens: TokenStream) -> TokenStream {
+    let sin_tokens = sin::TokenStream::from(tokens);
+    let text = TokenStream::from(sin_tokens).to_string();
+    format!("{text:?}").parse().unwrap()
+}
+
+#[proc_macro]
+pub fn sin_token_kinds(tokens: TokenStream) -> TokenStream {
+    let sin_tokens: sin::TokenStream = tokens.into();
+    let kinds: Vec<String> = sin_tokens
+        .iter()
+        .map(|tt| {
+            assert!(tt.span().span_data().span_id().is_some());
+            let kind = match &tt {
+                sin::TokenTree::Tree(_) => "group",
+                sin::TokenTree::Leaf(token, _) => match token {
+                    sin::Token::Ident(_) => "ident",
+                    sin::Token::Literal(_) => "literal",
+                    sin::Token::Delimiter(_) => "delimiter",
+                    sin::Token::Punct(_) => "punct",
+                    sin::Token::Keyword(_) => "keyword",
+                    sin::Token::CustomKeyword(_) => "custom keyword",
+                    sin::Token::Lifetime(_) => "lifetime",
+                    _ => "other",
+                },
+            };
+            format!("{:?}", format!("{kind} {}", tt.as_str()))
+        })
+        .collect();
+    format!("[{}]", kinds.join(", ")).parse().unwrap()
+}
+
+#[proc_macro]
+pub fn sin_quote_consts(tokens: TokenStream) -> TokenStream {
+    let sin_tokens: sin::TokenStream = tokens.into();
+    let names: Vec<sin::Ident> = sin_tokens
+        .iter()
+        .filter_map(|tt| match tt {
+            sin::TokenTree::Leaf(sin::Token::Ident(ident), span) => {
+                Some(sin::Ident::new_spanned(span, ident))
+            }
+            _ => None,
+        })
+        .collect();
+    let values = 0..names.len();
+    sin::quote!(#(pub const #names: usize = #values;)*).into()
+}
+
+/// Declares a unit struct for every ident.
+#[sin::proc_macro]
+pub fn sin_unit_structs(names: Vec<sin::Ident>) -> sin::ParseResult<impl sin::ToTokens> {
+    Ok(sin::quote!(#(pub struct #names;)*))
+}
+
+/// Appends `pub const NAME: usize = 1;` to the item, where `NAME` is the attribute argument.
+#[sin::proc_macro_attribute]
+pub fn sin_with_const(
+    name: sin::Ident,
+    item: Vec<sin::TokenTree>,
+) -> sin::ParseResult<sin::TokenStream> {
+    Ok(sin::quote!(#item pub const #name: usize = 1;))
+}
+
+/// Implements `NAME_LEN`, the length of the name of the struct it is applied to.
+#[sin::proc_macro_derive(SinNameLen)]
+pub fn derive_sin_name_len(item: Vec<sin::TokenTree>) -> sin::ParseResult<sin::TokenStream> {
+    let is_struct = |tt: &sin::TokenTree| tt.as_str() == "struct";
+    let Some(name) = item.iter().skip_while(|tt| !is_struct(tt)).nth(1) else {
+        return Err(sin::ParseError::new().error(sin::Span::call_site(), "expected a struct"));
+    };
+    let len = name.as_str().len();
+    Ok(sin::quote!(impl #name { pub const NAME_LEN: usize = #len; }))
+}
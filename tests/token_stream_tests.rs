@@ -0,0 +1,67 @@
+use test_macros::*;
+
+#[test]
+fn test_token_stream_round_trip() {
+    token_stream_round_trip!(a b c);
+    token_stream_round_trip!(x <<= 1 && y != 'c' || z >>= 2.5);
+    token_stream_round_trip! {
+        pub fn my_fn<'a, T: Something>(&'a self, something: T) -> Option<&'a T> {
+            let _ = [1u8, 2, 3];
+            match something { _ => None }
+        }
+    };
+}
+
+#[test]
+fn test_token_stream_identity() {
+    sin_identity! {
+        fn shifted<'a>(values: &'a [u8], _marker: &'a u8) -> u8 {
+            let mut x = values[0];
+            x <<= 2;
+            x
+        }
+    }
+    assert_eq!(shifted(&[3], &0), 12);
+    let mut count = 0;
+    sin_identity! {
+        'outer: for i in 0..10 {
+            for _ in 0..i {
+                count += 1;
+                if count == 4 {
+                    break 'outer;
+                }
+            }
+        }
+    }
+    assert_eq!(count, 4);
+    assert!(sin_identity!(count >= 4 && count % 2 == 0 || count < 0));
+}
+
+#[test]
+fn test_token_stream_kinds() {
+    assert_eq!(
+        sin_token_kinds!(struct Foo<'a> ..= "hi" _ { }),
+        [
+            "keyword struct",
+            "ident Foo",
+            "punct <",
+            "lifetime 'a",
+            "punct >",
+            "punct ..=",
+            "literal hi",
+            "punct _",
+            "group { }",
+        ]
+    );
+}
+
+#[test]
+fn test_token_stream_conversion_error() {
+    assert_eq!(sin_conversion_error!(a + 'b' <<= "c"), "");
+    assert!(sin_conversion_error!(let s = c"hi";).starts_with("unsupported literal `c\"hi\"`"));
+    // the infallible conversion reports the error from the expansion instead
+    assert_eq!(sin_lossy_conversion!(a + b), "a + b");
+    let text = sin_lossy_conversion!(let s = c"hi";);
+    assert!(text.starts_with(":: core :: compile_error!"));
+    assert!(text.contains("unsupported literal"));
+}